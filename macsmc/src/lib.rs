@@ -14,6 +14,15 @@
 //! ```
 //!
 //! See [`Smc`] for the starting point.
+//!
+//! # Features
+//!
+//! - `serde`: Implements `Serialize` and `Deserialize` for all value types.
+//!   The unit wrappers include their unit, e.g. `Celsius(42.0)` is `{"celsius": 42.0}` in JSON.
+//! - `uom`: Implements conversions between the unit wrappers and the `f64` quantities of the
+//!   [`uom`](https://docs.rs/uom) crate, e.g. from [`Celsius`] to `ThermodynamicTemperature`.
+//! - `tokio`: Adds `AsyncSmc`, which runs SMC calls on a dedicated thread and provides
+//!   `async` versions of the getters, as well as a stream of readings in a fixed interval.
 #![warn(anonymous_parameters)]
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
@@ -31,19 +40,31 @@ compile_error!("This crate only works on macOS");
 
 use std::{
     array::TryFromSliceError,
+    collections::{HashMap, HashSet},
     convert::{TryFrom, TryInto},
     error::Error as StdError,
     fmt::{self, Display},
+    fs, io,
+    marker::PhantomData,
     num::TryFromIntError,
-    ops::Deref,
-    time::Duration,
+    ops::{Deref, Mul, Neg, RangeInclusive, Sub},
+    path::Path,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc, Arc, Mutex, PoisonError,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant, SystemTime},
 };
 
 /// This crates result type
 pub type Result<T> = std::result::Result<T, Error>;
 
 /// Possible errors that can happen
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum Error {
     /// Signals that SMC is not available and that there is no easy way to resolve this.
     /// This could be because newer versions of macOS change the SMC API in a incompatible way
@@ -63,6 +84,9 @@ pub enum Error {
         /// The data type that this operation would provide
         tpe: u32,
     },
+    /// The requested sensor or key is not available on this machine.
+    /// This contains the catalog id or the SMC key that was requested.
+    KeyNotAvailable(String),
 }
 
 /// Temperature in Celsius (centigrade) scale.
@@ -75,6 +99,7 @@ pub enum Error {
 ///
 /// assert_eq!(*celsius, 42.0);
 /// assert_eq!(Into::<Fahrenheit>::into(celsius), Fahrenheit(107.6));
+/// assert_eq!(format!("{:.1}", celsius), "42.0 °C");
 /// ```
 #[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
 pub struct Celsius(pub f32);
@@ -121,11 +146,52 @@ impl From<Celsius> for Fahrenheit {
     }
 }
 
+impl From<Fahrenheit> for Celsius {
+    fn from(v: Fahrenheit) -> Self {
+        Self((v.0 - 32.0) * (5.0 / 9.0))
+    }
+}
+
+/// Temperature in Kelvin scale.
+///
+/// # Examples
+/// ```
+/// # use macsmc::{Celsius, Kelvin};
+/// let kelvin = Kelvin::from(Celsius(42.0));
+///
+/// assert_eq!(kelvin, Kelvin(315.15));
+/// assert_eq!(Celsius::from(kelvin), Celsius(42.0));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Kelvin(pub f32);
+
+impl Deref for Kelvin {
+    type Target = f32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<Celsius> for Kelvin {
+    fn from(v: Celsius) -> Self {
+        Self(v.0 + 273.15)
+    }
+}
+
+impl From<Kelvin> for Celsius {
+    fn from(v: Kelvin) -> Self {
+        Self(v.0 - 273.15)
+    }
+}
+
 impl Celsius {
     const THRESHOLDS: [Self; 4] = [Self(50.0), Self(68.0), Self(80.0), Self(90.0)];
 
     /// Thresholds that might be sensible to partition a temperature value
     /// into one of 4 buckets.
+    /// These are the same for every sensor, see [`ThresholdPolicy`] for thresholds
+    /// that depend on the sensor and the machine.
     ///
     /// # Examples
     /// ```
@@ -143,6 +209,7 @@ impl Celsius {
 /// Combination of various CPU Temperatures
 /// If a sensor is missing, the value is 0.0
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpuTemperatures {
     /// Temperature in CPU proximity. This is usually _the_ temperature, that would be shown for the CPU.
     pub proximity: Celsius,
@@ -155,9 +222,107 @@ pub struct CpuTemperatures {
     pub system_agent: Celsius,
 }
 
+/// The kind of a CPU core on hybrid chips that have performance and efficiency cores.
+/// On CPUs that only have a single kind of cores, all cores are performance cores.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum CoreKind {
+    /// A core of the performance cluster (P-core)
+    Performance,
+    /// A core of the efficiency cluster (E-core)
+    Efficiency,
+}
+
+/// The temperature of a single CPU core.
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreTemperature {
+    /// The cluster that this core belongs to
+    pub kind: CoreKind,
+    /// The index of this core within its cluster, starting at 0
+    pub index: u8,
+    /// The temperature of this core
+    pub temperature: Celsius,
+}
+
+/// Aggregates over the temperatures of all CPU cores.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// let cores = [
+///     CoreTemperature { kind: CoreKind::Performance, index: 0, temperature: Celsius(60.0) },
+///     CoreTemperature { kind: CoreKind::Performance, index: 1, temperature: Celsius(70.0) },
+///     CoreTemperature { kind: CoreKind::Efficiency, index: 0, temperature: Celsius(0.0) },
+/// ];
+/// let stats = CoreTemperatureStats::of(&cores).unwrap();
+/// assert_eq!(stats.hottest, cores[1]);
+/// assert_eq!(stats.mean, Celsius(65.0));
+/// assert_eq!(stats.spread, Celsius(10.0));
+/// assert_eq!(stats.count, 2);
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CoreTemperatureStats {
+    /// The hottest core
+    pub hottest: CoreTemperature,
+    /// The temperature of the coolest core
+    pub min: Celsius,
+    /// The temperature of the hottest core
+    pub max: Celsius,
+    /// The average temperature of all cores
+    pub mean: Celsius,
+    /// The difference between the hottest and the coolest core
+    pub spread: Celsius,
+    /// The number of cores that have a temperature
+    pub count: usize,
+}
+
+impl CoreTemperatureStats {
+    /// Aggregates the temperatures of the given cores.
+    /// Cores without a temperature reading (0.0) are skipped.
+    /// Returns None if no core has a temperature.
+    pub fn of<'a>(cores: impl IntoIterator<Item = &'a CoreTemperature>) -> Option<Self> {
+        let mut cores = cores.into_iter().filter(|core| *core.temperature > 0.0);
+        let first = *cores.next()?;
+        let mut stats = Self {
+            hottest: first,
+            min: first.temperature,
+            max: first.temperature,
+            mean: first.temperature,
+            spread: Celsius(0.0),
+            count: 1,
+        };
+        let mut sum = f64::from(*first.temperature);
+        for core in cores {
+            if core.temperature > stats.max {
+                stats.hottest = *core;
+                stats.max = core.temperature;
+            }
+            if core.temperature < stats.min {
+                stats.min = core.temperature;
+            }
+            sum += f64::from(*core.temperature);
+            stats.count += 1;
+        }
+        stats.mean = Celsius((sum / stats.count as f64) as f32);
+        stats.spread = Celsius(*stats.max - *stats.min);
+        Some(stats)
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct CpuCore {
+    kind: CoreKind,
+    index: u8,
+    key: CommandKey,
+}
+
 /// Combination of various CPU Temperatures
 /// If a sensor is missing, the value is 0.0
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GpuTemperatures {
     /// Temperature in GPU proximity. This is usually _the_ temperature, that would be shown for the GPU.
     /// Can be missing if there is no dedicated GPU.
@@ -166,10 +331,24 @@ pub struct GpuTemperatures {
     pub die: Celsius,
 }
 
+/// Various sensors of a single GPU.
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GpuDetail {
+    /// Temperature in GPU proximity. This is usually _the_ temperature, that would be shown for the GPU.
+    pub proximity: Celsius,
+    /// Temperature directly on the GPU Die. This is usually hotter than the proximity temperature.
+    pub die: Celsius,
+    /// The power on the rail that the GPU is running on
+    pub power: Watt,
+}
+
 /// Various other CPU temperatures.
 /// This list is not exhaustive nor are the sensors commonly available.
 /// If a sensor is missing, the value is 0.0
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OtherTemperatures {
     /// Memory Bank
     pub memory_bank_proximity: Celsius,
@@ -197,6 +376,30 @@ pub struct OtherTemperatures {
     pub palm_rest_2: Celsius,
 }
 
+/// Temperatures of a single memory module (DIMM).
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DimmTemperatures {
+    /// Temperature in proximity of the memory module
+    pub proximity: Celsius,
+    /// Temperature of the slot that the memory module is plugged into
+    pub slot: Celsius,
+}
+
+/// Temperatures of a single storage device (SSD or HDD).
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DriveTemperatures {
+    /// Temperature in proximity of the drive or its bay
+    pub proximity: Celsius,
+    /// Temperature of the first NAND sensor of an SSD
+    pub nand_1: Celsius,
+    /// Temperature of the second NAND sensor of an SSD
+    pub nand_2: Celsius,
+}
+
 /// Unit for fan speed (RPM = Revolutions per minute)
 ///
 /// # Examples
@@ -225,6 +428,7 @@ impl Into<f64> for Rpm {
 /// Collection of various speeds about a single fan.
 /// If a sensor is missing, the value is 0.0
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FanSpeed {
     /// The current, actual, speed.
     pub actual: Rpm,
@@ -263,6 +467,88 @@ impl FanSpeed {
         100.0 * pct
     }
 
+    /// The average speed of the given fans, as percentage of their max speeds.
+    /// Fans without a max speed are skipped.
+    /// Returns None if no fan has a max speed.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::{FanSpeed, Rpm};
+    /// let fan = |actual| FanSpeed {
+    ///     actual: Rpm(actual),
+    ///     max: Rpm(5000.0),
+    ///     ..FanSpeed::default()
+    /// };
+    /// let fans = [fan(1000.0), fan(3000.0), FanSpeed::default()];
+    ///
+    /// assert_eq!(FanSpeed::average_percentage(&fans), Some(40.0));
+    /// assert_eq!(FanSpeed::average_percentage(&[]), None);
+    /// ```
+    pub fn average_percentage<'a>(fans: impl IntoIterator<Item = &'a FanSpeed>) -> Option<f32> {
+        let (sum, count) = fans
+            .into_iter()
+            .filter(|fan| *fan.max > *fan.min)
+            .fold((0.0, 0_u32), |(sum, count), fan| {
+                (sum + f64::from(fan.percentage()), count + 1)
+            });
+        if count == 0 {
+            None
+        } else {
+            Some((sum / f64::from(count)) as f32)
+        }
+    }
+
+    /// The health of this fan, based on this single sample.
+    /// A fan that is spinning up, e.g. from 0 RPM when it is idle on Apple Silicon,
+    /// looks stalled or too slow for a moment. Use [`FanDiagnostics`] to only report
+    /// fans that stay in such a state, or that cannot reach their target speed.
+    ///
+    /// A fan is stalled if it spins slower than 100 RPM while its target is above its
+    /// minimum speed. Speeds more than 5% outside of `[min, max]` are reported as well,
+    /// unless the target is below the minimum, e.g. because the fan is turned off.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::{FanHealth, FanSpeed, Rpm};
+    /// let fan_speed = FanSpeed {
+    ///     actual: Rpm(0.0),
+    ///     min: Rpm(1200.0),
+    ///     max: Rpm(6000.0),
+    ///     target: Rpm(2000.0),
+    ///     ..FanSpeed::default()
+    /// };
+    ///
+    /// assert_eq!(fan_speed.health(), FanHealth::Stalled);
+    /// ```
+    pub fn health(&self) -> FanHealth {
+        const STALLED_RPM: f32 = 100.0;
+        const RANGE_TOLERANCE: f32 = 0.05;
+
+        if *self.actual < STALLED_RPM && *self.target > *self.min {
+            FanHealth::Stalled
+        } else if *self.max <= *self.min {
+            // min or max is missing
+            FanHealth::Ok
+        } else if *self.actual < *self.min * (1.0 - RANGE_TOLERANCE) && *self.target >= *self.min {
+            FanHealth::BelowMinimum
+        } else if *self.actual > *self.max * (1.0 + RANGE_TOLERANCE) {
+            FanHealth::AboveMaximum
+        } else {
+            FanHealth::Ok
+        }
+    }
+
+    /// Returns true if the fan is spinning at its target speed,
+    /// within 10% or 100 RPM, whichever is larger.
+    /// A fan without a target speed, or with a target of 0, always reaches it.
+    pub fn reaches_target(&self) -> bool {
+        if *self.target <= 0.0 {
+            return true;
+        }
+        let tolerance = (*self.target * 0.1).max(100.0);
+        (*self.actual - *self.target).abs() <= tolerance
+    }
+
     /// Speed threshold for this fan.
     /// This divides the [min, max] range into 3 equally sized segments.
     ///
@@ -290,6 +576,8 @@ impl FanSpeed {
 
 /// How a fan is being operated.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FanMode {
     /// The fan is in manual mode, its speed is a forced setting
     Forced,
@@ -313,8 +601,126 @@ impl Default for FanMode {
     }
 }
 
+/// The health of a single fan, as reported by [`FanSpeed::health`] and [`FanDiagnostics`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FanHealth {
+    /// The fan is working as expected
+    Ok,
+    /// The fan is not spinning, although its target speed is above its minimum speed
+    Stalled,
+    /// The fan is spinning slower than its minimum speed
+    BelowMinimum,
+    /// The fan is spinning faster than its maximum speed
+    AboveMaximum,
+    /// The fan did not reach its target speed for several samples in a row
+    MissesTarget,
+}
+
+impl FanHealth {
+    /// Returns true if the fan is working as expected.
+    pub fn is_ok(self) -> bool {
+        self == FanHealth::Ok
+    }
+}
+
+impl Display for FanHealth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            FanHealth::Ok => "OK",
+            FanHealth::Stalled => "Stalled",
+            FanHealth::BelowMinimum => "Below minimum speed",
+            FanHealth::AboveMaximum => "Above maximum speed",
+            FanHealth::MissesTarget => "Not reaching target speed",
+        })
+    }
+}
+
+/// Diagnoses fans over several samples, to find fans that are stalled, too slow,
+/// or cannot reach their target speed.
+/// A fan needs some time to spin up or down, so it is only reported once it
+/// was stalled, below its minimum, or missed its target for the given number of samples in a row.
+/// Fans that spin faster than their maximum speed are reported right away.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// let fan = FanSpeed {
+///     actual: Rpm(2000.0),
+///     min: Rpm(1200.0),
+///     max: Rpm(6000.0),
+///     target: Rpm(4000.0),
+///     ..FanSpeed::default()
+/// };
+/// let mut diagnostics = FanDiagnostics::new(3);
+/// assert_eq!(diagnostics.update(&[fan]), vec![FanHealth::Ok]);
+/// assert_eq!(diagnostics.update(&[fan]), vec![FanHealth::Ok]);
+/// assert!(diagnostics.is_pending());
+/// assert_eq!(diagnostics.update(&[fan]), vec![FanHealth::MissesTarget]);
+/// assert!(!diagnostics.is_pending());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FanDiagnostics {
+    samples: u32,
+    misses: Vec<u32>,
+}
+
+impl FanDiagnostics {
+    /// The number of samples that [`FanDiagnostics::default`] uses.
+    pub const DEFAULT_SAMPLES: u32 = 5;
+
+    /// Creates diagnostics that report a fan once it was stalled, below its minimum,
+    /// or missed its target for the given number of samples in a row.
+    pub fn new(samples: u32) -> Self {
+        Self {
+            samples: samples.max(1),
+            misses: Vec::new(),
+        }
+    }
+
+    /// Adds a sample of all fans and returns the health of every fan, in the same order.
+    /// Fans should be passed in the same order for every sample, e.g. as returned by [`Smc::fans`].
+    pub fn update(&mut self, fans: &[FanSpeed]) -> Vec<FanHealth> {
+        let samples = self.samples;
+        self.misses.resize(fans.len(), 0);
+        fans.iter()
+            .zip(self.misses.iter_mut())
+            .map(|(fan, misses)| {
+                let health = fan.health();
+                let missed = !fan.reaches_target()
+                    || matches!(health, FanHealth::Stalled | FanHealth::BelowMinimum);
+                *misses = if missed { *misses + 1 } else { 0 };
+                match health {
+                    FanHealth::Ok if *misses >= samples => FanHealth::MissesTarget,
+                    FanHealth::Stalled | FanHealth::BelowMinimum if *misses < samples => {
+                        FanHealth::Ok
+                    }
+                    health => health,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns true if a fan was stalled, too slow or missed its target in the last sample,
+    /// but not often enough to be reported yet.
+    pub fn is_pending(&self) -> bool {
+        self.misses
+            .iter()
+            .any(|&misses| misses > 0 && misses < self.samples)
+    }
+}
+
+impl Default for FanDiagnostics {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_SAMPLES)
+    }
+}
+
 /// Various information about the battery in general.
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BatteryInfo {
     /// `true` if the system is running on battery power
     pub battery_powered: bool,
@@ -334,6 +740,7 @@ pub struct BatteryInfo {
 
 /// Various information about the battery in detail
 #[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BatteryDetail {
     /// The number of charging cycles of the battery
     pub cycles: u32,
@@ -375,14 +782,9 @@ impl BatteryDetail {
     /// How much time is remaining on battery, based on the current current (amperage).
     /// This is not checking if the system is marked as being "powered by battery".
     /// This only operates based on the value of `amperage`.
-    /// Returns `None` if the battery is draining.
+    /// Returns `None` if the battery is not draining, i.e. if it is charging or idle.
     pub fn time_remaining(&self) -> Option<Duration> {
-        if *self.amperage >= 0 {
-            None
-        } else {
-            let hours = f64::from(*self.current_capacity) / f64::from(-*self.amperage);
-            Some(Duration::from_secs_f64(3600.0 * hours))
-        }
+        self.current_capacity.duration_at(-self.amperage)
     }
 
     /// How long it will take to load the battery based on the current current (amperage).
@@ -390,19 +792,14 @@ impl BatteryDetail {
     /// This only operates based on the value of `amperage`.
     /// Returns `None` if the battery is not charging.
     pub fn time_until_full(&self) -> Option<Duration> {
-        if *self.amperage <= 0 {
-            None
-        } else {
-            let hours =
-                f64::from(*self.full_capacity - *self.current_capacity) / f64::from(*self.amperage);
-            Some(Duration::from_secs_f64(3600.0 * hours))
-        }
+        (self.full_capacity - self.current_capacity).duration_at(self.amperage)
     }
 }
 
 /// Various power related values of the CPU.
 /// If a sensor is missing, the value is 0.0
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CpuPower {
     /// The power consumption of the CPU core
     pub core: Watt,
@@ -416,6 +813,156 @@ pub struct CpuPower {
     pub total: Watt,
 }
 
+impl CpuPower {
+    /// The power consumption of the whole CPU package.
+    /// This is the total power if it is available, or the sum of the core
+    /// and graphics unit otherwise.
+    /// The memory is not part of the package and is never included.
+    /// Returns None if none of these sensors are available.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::{CpuPower, Percent, Watt};
+    /// let power = CpuPower {
+    ///     core: Watt(6.0),
+    ///     dram: Watt(1.0),
+    ///     gfx: Watt(2.0),
+    ///     ..CpuPower::default()
+    /// };
+    /// assert_eq!(power.package(), Some(Watt(8.0)));
+    /// assert_eq!(power.uncore(), Some(Watt(2.0)));
+    /// assert_eq!(power.core_share(), Some(Percent(75.0)));
+    /// assert_eq!(power.uncore_share(), Some(Percent(25.0)));
+    /// ```
+    pub fn package(&self) -> Option<Watt> {
+        if *self.total > 0.0 {
+            return Some(self.total);
+        }
+        let sum = *self.core + *self.gfx;
+        if sum > 0.0 {
+            Some(Watt(sum))
+        } else {
+            None
+        }
+    }
+
+    /// The power consumption of the package that is not used by the cores,
+    /// e.g. by the memory unit, the graphics unit, or the system agent.
+    /// Returns None if the package or core power is not available.
+    pub fn uncore(&self) -> Option<Watt> {
+        let package = self.package()?;
+        if *self.core > 0.0 {
+            Some(Watt((*package - *self.core).max(0.0)))
+        } else {
+            None
+        }
+    }
+
+    /// The share of the package power that is used by the cores.
+    /// Returns None if the package or core power is not available.
+    pub fn core_share(&self) -> Option<Percent> {
+        let package = self.package()?;
+        let uncore = self.uncore()?;
+        Some(Percent(100.0 * (*package - *uncore) / *package))
+    }
+
+    /// The share of the package power that is not used by the cores.
+    /// Returns None if the package or core power is not available.
+    pub fn uncore_share(&self) -> Option<Percent> {
+        let package = self.package()?;
+        let uncore = self.uncore()?;
+        Some(Percent(100.0 * *uncore / *package))
+    }
+}
+
+/// Various voltages of the power rails.
+/// This list is not exhaustive nor are the sensors commonly available.
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Voltages {
+    /// The voltage of the CPU core (VID)
+    pub cpu_core: Volt,
+    /// The voltage of the CPU I/O unit (VccIO)
+    pub cpu_vccio: Volt,
+    /// The voltage of the CPU uncore unit (VccSA)
+    pub cpu_system_agent: Volt,
+    /// The voltage of the GPU core
+    pub gpu_core: Volt,
+    /// The voltage of the memory rail
+    pub memory: Volt,
+    /// The voltage of the Platform Controller Hub
+    pub platform_controller_hub: Volt,
+    /// The voltage on the DC input
+    pub dc_in: Volt,
+    /// The voltage of the main 12V rail
+    pub rail_12v: Volt,
+}
+
+/// Various currents on the power rails.
+/// This list is not exhaustive nor are the sensors commonly available.
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Currents {
+    /// The current of the CPU core
+    pub cpu_core: Ampere,
+    /// The current on the rail that the CPU is running on
+    pub cpu_rail: Ampere,
+    /// The current of the CPU I/O unit (VccIO)
+    pub cpu_vccio: Ampere,
+    /// The current of the CPU uncore unit (VccSA)
+    pub cpu_system_agent: Ampere,
+    /// The current of the GPU core
+    pub gpu_core: Ampere,
+    /// The current of the memory rail
+    pub memory: Ampere,
+    /// The current on the DC input
+    pub dc_in: Ampere,
+    /// The current that is delivered by the battery charger
+    pub charger: Ampere,
+}
+
+/// A reading of a single ambient light sensor.
+/// The channels are raw sensor counts and are not calibrated to lux.
+/// If the sensor is missing, the reading is not valid and the channels are 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LightSensor {
+    /// Whether the sensor reported a valid reading
+    pub valid: bool,
+    /// Whether the sensor is running in its high gain mode, e.g. in a dark room
+    pub high_gain: bool,
+    /// The raw count of the broadband channel (visible and infrared light)
+    pub broadband: u16,
+    /// The raw count of the infrared channel
+    pub infrared: u16,
+}
+
+/// Readings of the ambient light sensors.
+/// If a sensor is missing, the reading is not valid and the channels are 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AmbientLight {
+    /// The left ambient light sensor (`ALV0` key)
+    pub left: LightSensor,
+    /// The right ambient light sensor (`ALV1` key)
+    pub right: LightSensor,
+}
+
+/// Raw axes of the sudden motion sensor, which is only available on older models.
+/// If a sensor is missing, the value is 0.0
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Motion {
+    /// The acceleration along the X axis
+    pub x: f32,
+    /// The acceleration along the Y axis
+    pub y: f32,
+    /// The acceleration along the Z axis
+    pub z: f32,
+}
+
 /// Value wrapper for values that are mAh units
 ///
 /// # Examples
@@ -423,6 +970,7 @@ pub struct CpuPower {
 /// # use macsmc::MilliAmpereHours;
 /// let mah = MilliAmpereHours(42);
 /// assert_eq!(*mah, 42);
+/// assert_eq!(mah.to_string(), "42 mAh");
 /// ```
 #[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
 pub struct MilliAmpereHours(pub u32);
@@ -435,6 +983,52 @@ impl Deref for MilliAmpereHours {
     }
 }
 
+impl MilliAmpereHours {
+    /// How long this charge lasts at the given current.
+    /// Returns `None` if the current is not positive.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::{MilliAmpere, MilliAmpereHours};
+    /// # use std::time::Duration;
+    /// let charge = MilliAmpereHours(1000);
+    /// assert_eq!(charge.duration_at(MilliAmpere(500)), Some(Duration::from_secs(7200)));
+    /// assert_eq!(charge.duration_at(MilliAmpere(-500)), None);
+    /// ```
+    pub fn duration_at(self, current: MilliAmpere) -> Option<Duration> {
+        if current.0 <= 0 {
+            None
+        } else {
+            let hours = f64::from(self.0) / f64::from(current.0);
+            Some(Duration::from_secs_f64(3600.0 * hours))
+        }
+    }
+}
+
+/// Stops at 0 mAh instead of overflowing.
+impl Sub for MilliAmpereHours {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(self.0.saturating_sub(rhs.0))
+    }
+}
+
+/// Charge times voltage is energy.
+///
+/// # Examples
+/// ```
+/// # use macsmc::{MilliAmpereHours, Volt, WattHours};
+/// assert_eq!(MilliAmpereHours(5000) * Volt(12.0), WattHours(60.0));
+/// ```
+impl Mul<Volt> for MilliAmpereHours {
+    type Output = WattHours;
+
+    fn mul(self, rhs: Volt) -> Self::Output {
+        WattHours(self.0 as f32 / 1000.0 * rhs.0)
+    }
+}
+
 /// Value wrapper for values that are mA units
 ///
 /// # Examples
@@ -454,6 +1048,14 @@ impl Deref for MilliAmpere {
     }
 }
 
+impl Neg for MilliAmpere {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
+    }
+}
+
 /// Value wrapper for values that are V units
 ///
 /// # Examples
@@ -473,18 +1075,49 @@ impl Deref for Volt {
     }
 }
 
-/// Value wrapper for values that are W units
+/// Voltage times current is power.
 ///
 /// # Examples
 /// ```
-/// # use macsmc::Watt;
-/// let w = Watt(42.0);
-/// assert_eq!(*w, 42.0);
+/// # use macsmc::{Ampere, Volt, Watt};
+/// assert_eq!(Volt(12.0) * Ampere(1.5), Watt(18.0));
+/// ```
+impl Mul<Ampere> for Volt {
+    type Output = Watt;
+
+    fn mul(self, rhs: Ampere) -> Self::Output {
+        Watt(self.0 * rhs.0)
+    }
+}
+
+impl Mul<MilliAmpere> for Volt {
+    type Output = Watt;
+
+    fn mul(self, rhs: MilliAmpere) -> Self::Output {
+        self * Ampere::from(rhs)
+    }
+}
+
+impl Mul<MilliAmpereHours> for Volt {
+    type Output = WattHours;
+
+    fn mul(self, rhs: MilliAmpereHours) -> Self::Output {
+        rhs * self
+    }
+}
+
+/// Value wrapper for values that are A units
+///
+/// # Examples
+/// ```
+/// # use macsmc::Ampere;
+/// let a = Ampere(4.2);
+/// assert_eq!(*a, 4.2);
 /// ```
 #[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
-pub struct Watt(pub f32);
+pub struct Ampere(pub f32);
 
-impl Deref for Watt {
+impl Deref for Ampere {
     type Target = f32;
 
     fn deref(&self) -> &Self::Target {
@@ -492,1362 +1125,6598 @@ impl Deref for Watt {
     }
 }
 
-impl Into<f64> for Watt {
-    fn into(self) -> f64 {
-        f64::from(self.0)
+impl From<MilliAmpere> for Ampere {
+    fn from(v: MilliAmpere) -> Self {
+        Self(v.0 as f32 / 1000.0)
     }
 }
 
-impl Watt {
-    const THRESHOLDS: [Self; 4] = [Self(35.0), Self(50.0), Self(70.0), Self(85.0)];
+impl Mul<Volt> for Ampere {
+    type Output = Watt;
 
-    /// Thresholds that might be sensible to partition a power value
-    /// into one of 4 buckets.
-    ///
-    /// # Examples
-    /// ```
-    /// # use macsmc::Watt;
-    /// let huge = Watt::thresholds()[3];
-    /// let lot = Watt::thresholds()[2];
-    /// let some = Watt::thresholds()[1];
-    /// let little = Watt::thresholds()[0];
-    /// ```
-    pub fn thresholds() -> [Self; 4] {
-        Self::THRESHOLDS
+    fn mul(self, rhs: Volt) -> Self::Output {
+        rhs * self
     }
 }
 
-/// Raw data value from a sensor
-#[derive(Clone, Debug, PartialEq)]
-pub enum DataValue {
-    /// true/false value
-    Flag(bool),
-    /// float value
-    Float(f32),
-    /// unsigned integer
-    Int(i64),
-    /// signed integer
-    Uint(u64),
-    /// possible a string
-    Str(String),
-    /// Any other type that could not be decoded, containing its bytes
-    Unknown(Vec<u8>),
-}
+/// Value wrapper for values that are percentages, from 0.0 to 100.0
+///
+/// # Examples
+/// ```
+/// # use macsmc::Percent;
+/// let p = Percent(42.0);
+/// assert_eq!(*p, 42.0);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Percent(pub f32);
 
-/// Return type for a debug command. Does not interpret the data.
-#[derive(Debug)]
-pub struct Dbg {
-    /// The key for the data
-    pub key: String,
-    /// An error if the data could not be fetched
-    /// None if the key does not exist
-    /// Some(value) for other cases
-    pub value: Result<Option<DataValue>>,
+impl Deref for Percent {
+    type Target = f32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
 }
 
-/// Return type for a debug command. Does not interpret the data.
-#[derive(Debug)]
-pub struct DbgKeyInfo {
-    /// The key for the data
-    pub key: String,
-    /// The expected type of the data
-    pub data_type: String,
-    /// The expected number of bytes to read for the data
-    pub data_size: usize,
+impl From<Percent> for f64 {
+    fn from(value: Percent) -> Self {
+        f64::from(value.0)
+    }
 }
 
-/// The SMC client.
-/// All methods take self as a mutable reference, even though
-/// it is _technically_ not required.
-/// This is to make sure, that a single connection can only be used
-/// by one reference at a time.
+/// Value wrapper for values that are W units
 ///
 /// # Examples
 /// ```
-/// # use macsmc::*;
-/// # fn main() -> Result<()> {
-/// let mut smc = Smc::connect()?;
-/// let cpu_temp = smc.cpu_temperature()?;
-/// assert!(*cpu_temp.proximity > 0.0);
-/// // will disconnect
-/// drop(smc);
-/// # Ok(())
-/// # }
+/// # use macsmc::Watt;
+/// let w = Watt(42.0);
+/// assert_eq!(*w, 42.0);
 /// ```
-#[cfg_attr(doc, doc(cfg(target_os = "macos")))]
-#[derive(Debug)]
-pub struct Smc {
-    inner: cffi::SMCConnection,
-}
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Watt(pub f32);
 
-impl Smc {
-    #![cfg_attr(doc, doc(cfg(target_os = "macos")))]
+impl Deref for Watt {
+    type Target = f32;
 
-    /// Creates a new connection to the SMC system.
-    ///
-    /// # Errors
-    /// [`Error::SmcNotAvailable`] If the SMC system is not available
-    pub fn connect() -> Result<Self> {
-        let inner = cffi::SMCConnection::new()?;
-        Ok(Smc { inner })
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    /// Returns an iterator over all [FanSpeed](struct.FanSpeed.html) items available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn fans(&mut self) -> Result<FanIter> {
-        FanIter::new(self)
+impl Into<f64> for Watt {
+    fn into(self) -> f64 {
+        f64::from(self.0)
     }
+}
 
-    fn number_of_fans(&mut self) -> Result<u8> {
-        Ok(self.inner.read_value(GetNumberOfFans)?)
+impl Neg for Watt {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self(-self.0)
     }
+}
 
-    fn fan_speed(&mut self, fan: u8) -> Result<FanSpeed> {
-        let actual = self.inner.read_value(GetActualFanSpeed(fan))?;
-        let min = self.inner.read_value(GetMinFanSpeed(fan))?;
-        let max = self.inner.read_value(GetMaxFanSpeed(fan))?;
-        let target = self.inner.read_value(GetTargetFanSpeed(fan))?;
-        let safe = self.inner.read_value(GetSafeFanSpeed(fan))?;
-        let mode = self.inner.read_value(GetFanMode(fan))?;
-        Ok(FanSpeed {
-            actual,
-            min,
-            max,
-            target,
-            safe,
-            mode,
-        })
+/// Power over time is energy.
+///
+/// # Examples
+/// ```
+/// # use macsmc::{Joules, Watt};
+/// # use std::time::Duration;
+/// assert_eq!(Watt(20.0) * Duration::from_secs(60), Joules(1200.0));
+/// ```
+impl Mul<Duration> for Watt {
+    type Output = Joules;
+
+    fn mul(self, rhs: Duration) -> Self::Output {
+        Joules(self.0 * rhs.as_secs_f32())
     }
+}
 
-    /// Returns the overall [`BatteryInfo`]
+impl Watt {
+    const THRESHOLDS: [Self; 4] = [Self(35.0), Self(50.0), Self(70.0), Self(85.0)];
+
+    /// Thresholds that might be sensible to partition a power value
+    /// into one of 4 buckets.
+    /// These are the same for every sensor, see [`ThresholdPolicy`] for thresholds
+    /// that depend on the sensor and the machine.
     ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn battery_info(&mut self) -> Result<BatteryInfo> {
-        let BatteryStatus {
-            charging,
-            ac_present,
-            health_ok,
-        } = self.inner.read_value(GetBatteryInfo)?;
-        let battery_powered = self.inner.read_value(IsBatteryPowered)?;
-        let temperature_max = self.inner.read_value(GetBatteryTemperatureMax)?;
-        let temperature_1 = self.inner.read_value(GetBatteryTemperature1)?;
-        let temperature_2 = self.inner.read_value(GetBatteryTemperature2)?;
-        Ok(BatteryInfo {
-            battery_powered,
-            charging,
-            ac_present,
-            health_ok,
-            temperature_max,
-            temperature_1,
-            temperature_2,
-        })
+    /// # Examples
+    /// ```
+    /// # use macsmc::Watt;
+    /// let huge = Watt::thresholds()[3];
+    /// let lot = Watt::thresholds()[2];
+    /// let some = Watt::thresholds()[1];
+    /// let little = Watt::thresholds()[0];
+    /// ```
+    pub fn thresholds() -> [Self; 4] {
+        Self::THRESHOLDS
     }
+}
 
-    fn number_of_batteries(&mut self) -> Result<u8> {
-        Ok(self.inner.read_value(GetNumberOfBatteries)?)
+/// Value wrapper for values that are Wh units
+///
+/// # Examples
+/// ```
+/// # use macsmc::{Joules, WattHours};
+/// let wh = WattHours(42.0);
+/// assert_eq!(*wh, 42.0);
+/// assert_eq!(Joules::from(wh), Joules(151_200.0));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct WattHours(pub f32);
+
+impl Deref for WattHours {
+    type Target = f32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    /// Returns an iterator over all [`BatteryDetail`] items available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn battery_details(&mut self) -> Result<BatteryIter> {
-        Ok(BatteryIter::new(self)?)
+impl From<Joules> for WattHours {
+    fn from(v: Joules) -> Self {
+        Self(v.0 / 3600.0)
     }
+}
 
-    fn battery_detail(&mut self, battery: u8) -> Result<BatteryDetail> {
-        let cycles = self.inner.read_value(GetBatteryCycleCount(battery))?;
-        let current_capacity = self.inner.read_value(GetBatteryCurrentCapacity(battery))?;
-        let full_capacity = self.inner.read_value(GetBatteryFullCapacity(battery))?;
-        let amperage = self.inner.read_value(GetBatteryAmperage(battery))?;
-        let voltage = self.inner.read_value(GetBatteryVoltage(battery))?;
-        let power = self.inner.read_value(GetBatteryPower(battery))?;
-        Ok(BatteryDetail {
-            cycles,
-            current_capacity,
-            full_capacity,
-            amperage,
-            voltage,
-            power,
-        })
+/// Value wrapper for values that are J units
+///
+/// # Examples
+/// ```
+/// # use macsmc::Joules;
+/// let j = Joules(42.0);
+/// assert_eq!(*j, 42.0);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, PartialOrd)]
+pub struct Joules(pub f32);
+
+impl Deref for Joules {
+    type Target = f32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
+}
 
-    #[cfg(target_os = "macos")]
-    fn number_of_cpus(&mut self) -> Result<u8> {
-        Ok(cffi::num_cpus().min(255) as u8)
+impl From<WattHours> for Joules {
+    fn from(v: WattHours) -> Self {
+        Self(v.0 * 3600.0)
     }
+}
 
-    /// Returns the overall [`CpuTemperatures`] available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn cpu_temperature(&mut self) -> Result<CpuTemperatures> {
-        let proximity = self.inner.read_value(CpuProximityTemperature)?;
-        let die = self.inner.read_value(CpuDieTemperature)?;
-        let graphics = self.inner.read_value(CpuGfxTemperature)?;
-        let system_agent = self.inner.read_value(CpuSystemAgentTemperature)?;
-        Ok(CpuTemperatures {
-            proximity,
-            die,
-            graphics,
-            system_agent,
+// Displays the value followed by the unit, any formatting options apply to the value.
+macro_rules! unit_display {
+    ($($unit:ident = $suffix:literal),+ $(,)?) => {$(
+        impl Display for $unit {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                Display::fmt(&self.0, f)?;
+                f.write_str(concat!(" ", $suffix))
+            }
+        }
+    )+};
+}
+
+unit_display! {
+    Celsius = "°C",
+    Fahrenheit = "°F",
+    Kelvin = "K",
+    Rpm = "RPM",
+    MilliAmpereHours = "mAh",
+    MilliAmpere = "mA",
+    Volt = "V",
+    Ampere = "A",
+    Percent = "%",
+    Watt = "W",
+    WattHours = "Wh",
+    Joules = "J",
+}
+
+/// Errors that can happen when parsing a unit value like `85°C` or `3000rpm`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseUnitError {
+    /// The input was empty
+    Empty,
+    /// The input did not start with a number
+    InvalidNumber {
+        /// The input that was parsed
+        input: String,
+    },
+    /// The unit is not one that can be converted into the requested type
+    UnknownUnit {
+        /// The input that was parsed
+        input: String,
+        /// The unit that was not known
+        unit: String,
+        /// The units that would have been accepted
+        expected: &'static str,
+    },
+}
+
+impl StdError for ParseUnitError {}
+
+impl Display for ParseUnitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseUnitError::Empty => f.write_str("Cannot parse a value from an empty string"),
+            ParseUnitError::InvalidNumber { input } => {
+                write!(f, "The value `{}` does not start with a number", input)
+            }
+            ParseUnitError::UnknownUnit {
+                input,
+                unit,
+                expected,
+            } => write!(
+                f,
+                "Unknown unit `{}` in `{}`, expected one of {}",
+                unit, input, expected
+            ),
+        }
+    }
+}
+
+/// Splits the input into the number and the lowercased unit, without any whitespace
+/// or degree signs. A missing unit is returned as an empty string.
+/// The number is parsed as `f64`, so that converting it does not add rounding errors.
+fn split_unit(input: &str) -> std::result::Result<(f64, String), ParseUnitError> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(ParseUnitError::Empty);
+    }
+    let mut split = input
+        .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+'))
+        .unwrap_or(input.len());
+    // an exponent like `1e3` is part of the number if it has any digits,
+    // otherwise the `e` is the start of the unit
+    if let Some(b'e') | Some(b'E') = input.as_bytes().get(split) {
+        let exponent = &input.as_bytes()[split + 1..];
+        let sign = usize::from(matches!(exponent.first(), Some(b'-') | Some(b'+')));
+        let digits = exponent[sign..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits > 0 {
+            split += 1 + sign + digits;
+        }
+    }
+    let number = input[..split]
+        .parse::<f64>()
+        .map_err(|_| ParseUnitError::InvalidNumber {
+            input: input.to_string(),
+        })?;
+    let unit = input[split..]
+        .chars()
+        .filter_map(|c| match c {
+            '°' | 'º' | '˚' => None,
+            '℃' => Some('c'),
+            '℉' => Some('f'),
+            '\u{212a}' => Some('k'),
+            c if c.is_whitespace() => None,
+            c => Some(c.to_ascii_lowercase()),
         })
+        .collect();
+    Ok((number, unit))
+}
+
+/// Parses a value in one of the given units, each with the factor to the requested unit.
+fn parse_scaled(
+    input: &str,
+    units: &[(&str, f64)],
+    expected: &'static str,
+) -> std::result::Result<f32, ParseUnitError> {
+    let (number, unit) = split_unit(input)?;
+    match units.iter().find(|(name, _)| *name == unit) {
+        Some((_, factor)) => Ok((number * factor) as f32),
+        None => Err(ParseUnitError::UnknownUnit {
+            input: input.trim().to_string(),
+            unit,
+            expected,
+        }),
     }
+}
 
-    /// Returns an iterator over all cpu core temperatures in [`Celsius`].
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    #[cfg(any(doc, target_os = "macos"))]
-    pub fn cpu_core_temps(&mut self) -> Result<CpuIter> {
-        Ok(CpuIter::new(self)?)
+/// Parses a temperature in any scale into degrees Celsius,
+/// the scale defaults to the requested one.
+fn parse_temperature(input: &str, default: &str) -> std::result::Result<f64, ParseUnitError> {
+    let (number, unit) = split_unit(input)?;
+    let unit = if unit.is_empty() { default } else { &unit };
+    match unit {
+        "c" | "celsius" => Ok(number),
+        "f" | "fahrenheit" => Ok((number - 32.0) * (5.0 / 9.0)),
+        "k" | "kelvin" => Ok(number - 273.15),
+        _ => Err(ParseUnitError::UnknownUnit {
+            input: input.trim().to_string(),
+            unit: unit.to_string(),
+            expected: "°C, °F, K",
+        }),
     }
+}
+
+/// Parses a temperature in any scale, e.g. `85`, `85C`, `85 °C`, `185°F` or `358.15K`.
+///
+/// # Examples
+/// ```
+/// # use macsmc::Celsius;
+/// assert_eq!("85°C".parse(), Ok(Celsius(85.0)));
+/// assert_eq!("185 °F".parse(), Ok(Celsius(85.0)));
+/// assert!("85°X".parse::<Celsius>().is_err());
+/// ```
+impl FromStr for Celsius {
+    type Err = ParseUnitError;
 
-    fn cpu_core_temperature(&mut self, core: u8) -> Result<Celsius> {
-        Ok(self.inner.read_value(CpuCoreTemperature(core + 1))?)
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_temperature(s, "c").map(|celsius| Self(celsius as f32))
     }
+}
 
-    /// Returns the overall [`GpuTemperatures`] available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn gpu_temperature(&mut self) -> Result<GpuTemperatures> {
-        let proximity = self.inner.read_value(GpuProximityTemperature)?;
-        let die = self.inner.read_value(GpuDieTemperature)?;
-        Ok(GpuTemperatures { proximity, die })
+/// Parses a temperature in any scale, a number without a unit is in Fahrenheit.
+impl FromStr for Fahrenheit {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_temperature(s, "f").map(|celsius| Self((celsius * (9.0 / 5.0) + 32.0) as f32))
     }
+}
 
-    /// Returns the overall information about [`OtherTemperatures`] available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn other_temperatures(&mut self) -> Result<OtherTemperatures> {
-        let memory_bank_proximity = self.inner.read_value(GetMemoryBankProximityTemperature)?;
-        let mainboard_proximity = self.inner.read_value(GetMainboardProximityTemperature)?;
-        let platform_controller_hub_die = self.inner.read_value(GetPCHDieTemperature)?;
-        let airport = self.inner.read_value(GetAirportTemperature)?;
-        let airflow_left = self.inner.read_value(GetAirflowLeftTemperature)?;
-        let airflow_right = self.inner.read_value(GetAirflowRightTemperature)?;
-        let thunderbolt_left = self.inner.read_value(GetThunderboltLeftTemperature)?;
-        let thunderbolt_right = self.inner.read_value(GetThunderboltRightTemperature)?;
-        let heatpipe_1 = self.inner.read_value(GetHeatpipe1Temperature)?;
-        let heatpipe_2 = self.inner.read_value(GetHeatpipe2Temperature)?;
-        let palm_rest_1 = self.inner.read_value(GetPalmRest1Temperature)?;
-        let palm_rest_2 = self.inner.read_value(GetPalmRest2Temperature)?;
-        Ok(OtherTemperatures {
-            memory_bank_proximity,
-            mainboard_proximity,
-            platform_controller_hub_die,
-            airport,
-            airflow_left,
-            airflow_right,
-            thunderbolt_left,
-            thunderbolt_right,
-            heatpipe_1,
-            heatpipe_2,
-            palm_rest_1,
-            palm_rest_2,
-        })
+/// Parses a temperature in any scale, a number without a unit is in Kelvin.
+impl FromStr for Kelvin {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_temperature(s, "k").map(|celsius| Self((celsius + 273.15) as f32))
     }
+}
 
-    /// Returns the overall [`CpuPower`] information available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn cpu_power(&mut self) -> Result<CpuPower> {
-        let core = self.inner.read_value(CpuCorePower)?;
-        let dram = self.inner.read_value(CpuDramPower)?;
-        let gfx = self.inner.read_value(CpuGfxPower)?;
-        let rail = self.inner.read_value(CpuRailPower)?;
-        let total = self.inner.read_value(CpuTotalPower)?;
-        Ok(CpuPower {
-            core,
-            dram,
-            gfx,
-            rail,
-            total,
-        })
-    }
+/// Parses a fan speed, e.g. `3000` or `3000rpm`.
+impl FromStr for Rpm {
+    type Err = ParseUnitError;
 
-    /// Returns the overall `GPUPower` information in [`Watt`] available.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn gpu_power(&mut self) -> Result<Watt> {
-        Ok(self.inner.read_value(GpuRailPower)?)
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_scaled(s, &[("", 1.0), ("rpm", 1.0)], "RPM").map(Rpm)
     }
+}
 
-    /// Returns the current amount of power being in [`Watt`] drawn from DC.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn power_dc_in(&mut self) -> Result<Watt> {
-        Ok(self.inner.read_value(DcInPower)?)
+/// Parses a power, e.g. `45`, `45W` or `500mW`.
+impl FromStr for Watt {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let units = [
+            ("", 1.0),
+            ("w", 1.0),
+            ("watt", 1.0),
+            ("watts", 1.0),
+            ("mw", 0.001),
+            ("kw", 1000.0),
+        ];
+        parse_scaled(s, &units, "W, mW, kW").map(Watt)
     }
+}
 
-    /// Returns the overall power draw in [`Watt`] of the whole system.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn power_system_total(&mut self) -> Result<Watt> {
-        Ok(self.inner.read_value(SystemTotalPower)?)
+/// Parses a voltage, e.g. `12`, `12V` or `1200mV`.
+impl FromStr for Volt {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let units = [
+            ("", 1.0),
+            ("v", 1.0),
+            ("volt", 1.0),
+            ("volts", 1.0),
+            ("mv", 0.001),
+        ];
+        parse_scaled(s, &units, "V, mV").map(Volt)
     }
+}
 
-    /// Returns the number of available keys to query.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn number_of_keys(&mut self) -> Result<u32> {
-        Ok(self.inner.read_value(NumberOfKeys)?)
+/// Parses a current, e.g. `1.5`, `1.5A` or `1500mA`.
+impl FromStr for Ampere {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let units = [
+            ("", 1.0),
+            ("a", 1.0),
+            ("amp", 1.0),
+            ("amps", 1.0),
+            ("ampere", 1.0),
+            ("amperes", 1.0),
+            ("ma", 0.001),
+            ("milliamp", 0.001),
+            ("milliamps", 0.001),
+        ];
+        parse_scaled(s, &units, "A, mA").map(Ampere)
     }
+}
 
-    /// Returns an iterator over the available keys.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn all_keys(&mut self) -> Result<KeysIter> {
-        KeysIter::new(self)
+/// Parses a current, e.g. `1500`, `1500mA` or `1.5A`.
+///
+/// # Examples
+/// ```
+/// # use macsmc::MilliAmpere;
+/// assert_eq!("1.5 A".parse(), Ok(MilliAmpere(1500)));
+/// assert_eq!("-250mA".parse(), Ok(MilliAmpere(-250)));
+/// ```
+impl FromStr for MilliAmpere {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let units = [
+            ("", 1.0),
+            ("ma", 1.0),
+            ("milliamp", 1.0),
+            ("milliamps", 1.0),
+            ("a", 1000.0),
+            ("amp", 1000.0),
+            ("amps", 1000.0),
+            ("ampere", 1000.0),
+            ("amperes", 1000.0),
+        ];
+        let milliamperes = parse_scaled(s, &units, "mA, A")?;
+        Ok(MilliAmpere(milliamperes.round() as i32))
     }
+}
 
-    /// Returns an iterator over the available data points.
-    ///
-    /// # Errors
-    /// [`Error::DataError`] If there was something wrong while getting the data
-    pub fn all_data(&mut self) -> Result<DataIter> {
-        DataIter::new(self)
+/// Parses a percentage, e.g. `50` or `50%`.
+impl FromStr for Percent {
+    type Err = ParseUnitError;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        parse_scaled(s, &[("", 1.0), ("%", 1.0)], "%").map(Percent)
     }
+}
 
-    fn key_info_by_index(&mut self, index: u32) -> Result<DbgKeyInfo> {
-        let info = self.inner.key_info_by_index(index)?;
-        let key = info.key.to_be_bytes();
-        let key = std::str::from_utf8(&key).map_err(|_| InternalError::DataError {
-            key: info.key,
-            tpe: info.data_type,
-        })?;
-        self.key_info(key)
+/// Serialization of the unit wrappers.
+/// The unit is part of the serialized form, e.g. `Celsius(42.0)` is `{"celsius": 42.0}` in JSON.
+#[cfg(feature = "serde")]
+mod serde_units {
+    use super::*;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    macro_rules! unit_serde {
+        ($($unit:ident($inner:ty) = $name:literal),+ $(,)?) => {$(
+            impl Serialize for $unit {
+                fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                    #[derive(Serialize)]
+                    struct Repr {
+                        #[serde(rename = $name)]
+                        value: $inner,
+                    }
+                    Repr { value: self.0 }.serialize(serializer)
+                }
+            }
+
+            impl<'de> Deserialize<'de> for $unit {
+                fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                    #[derive(Deserialize)]
+                    #[serde(deny_unknown_fields)]
+                    struct Repr {
+                        #[serde(rename = $name)]
+                        value: $inner,
+                    }
+                    Repr::deserialize(deserializer).map(|repr| $unit(repr.value))
+                }
+            }
+        )+};
     }
 
-    fn key_data_by_index(&mut self, index: u32) -> Result<Dbg> {
-        let info = self.inner.key_info_by_index(index)?;
-        let key = info.key.to_be_bytes();
-        let key = std::str::from_utf8(&key).map_err(|_| InternalError::DataError {
-            key: info.key,
-            tpe: info.data_type,
-        })?;
-        Ok(self.check(key))
+    unit_serde! {
+    Celsius(f32) = "celsius",
+    Fahrenheit(f32) = "fahrenheit",
+    Kelvin(f32) = "kelvin",
+    Rpm(f32) = "rpm",
+    MilliAmpereHours(u32) = "milliampere_hours",
+    MilliAmpere(i32) = "milliampere",
+    Volt(f32) = "volt",
+    Ampere(f32) = "ampere",
+    Percent(f32) = "percent",
+    Watt(f32) = "watt",
+    WattHours(f32) = "watt_hours",
+    Joules(f32) = "joules",
     }
+}
 
-    fn key_info(&mut self, name: &str) -> Result<DbgKeyInfo> {
-        let info = self.inner.key_info(Check(name))?;
-        let key = info.key.to_be_bytes();
-        let tpe = info.data_type.to_be_bytes();
+/// Conversions between the unit wrappers and the matching `uom` quantities.
+/// The quantities use `f64`, so converting a wrapper into a quantity and back is lossless,
+/// except for temperatures within a billionth of a degree of zero, which become zero.
+/// Converting a quantity into a wrapper rounds to the precision of the wrapper.
+#[cfg(feature = "uom")]
+mod uom_units {
+    use super::*;
+    use uom::si::{
+        angular_velocity::revolution_per_minute,
+        electric_charge::milliampere_hour,
+        electric_current::{ampere, milliampere},
+        electric_potential::volt,
+        energy::{joule, watt_hour},
+        f64::{
+            AngularVelocity, ElectricCharge, ElectricCurrent, ElectricPotential, Energy, Power,
+            Ratio, ThermodynamicTemperature,
+        },
+        power::watt,
+        ratio::percent,
+        thermodynamic_temperature::{degree_celsius, degree_fahrenheit, kelvin},
+    };
 
-        Ok(DbgKeyInfo {
-            key: String::from_utf8_lossy(&key).to_string(),
-            data_type: String::from_utf8_lossy(&tpe).to_string(),
-            data_size: info.data_size.try_into().unwrap_or(usize::max_value()),
-        })
+    fn value(value: f64) -> f32 {
+        value as f32
     }
 
-    fn check(&mut self, name: &str) -> Dbg {
-        let value = self.inner.opt_read_value(Check(name));
-        Dbg {
-            key: name.to_string(),
-            value: value.map_err(Error::from),
+    /// The offset of Celsius and Fahrenheit leaves a rounding error of about 1e-13
+    /// in values around zero, which would otherwise not become zero again.
+    fn temperature(value: f64) -> f32 {
+        if value.abs() < 1e-9 {
+            0.0
+        } else {
+            value as f32
         }
     }
-}
-
-macro_rules! iter_impl {
-    ( $(#[$meta:meta])*
-    $struct:ident($range:tt) = $max:ident : $get:ident -> $out:tt) => {
-        $(#[$meta])*
-        /// Advancing this iterator by calling `nth` is a O(1) operation
-        /// and will not query all intermediate keys.
-        #[derive(Debug)]
-        pub struct $struct<'a> {
-            smc: &'a mut $crate::Smc,
-            next: $range,
-            max: $range,
-        }
 
-        impl<'a> $struct<'a> {
-            fn new(smc: &'a mut $crate::Smc) -> $crate::Result<Self> {
-                let max = $range::from(smc.$max()?);
-                Ok(Self { smc, next: 0, max })
-            }
-        }
+    fn signed(value: f64) -> i32 {
+        value.round() as i32
+    }
 
-        impl<'a> Iterator for $struct<'a> {
-            type Item = $crate::Result<$out>;
+    fn unsigned(value: f64) -> u32 {
+        value.round() as u32
+    }
 
-            fn next(&mut self) -> Option<Self::Item> {
-                if self.next >= self.max {
-                    return None;
+    macro_rules! uom_conversion {
+        ($($unit:ident = $quantity:ident in $uom_unit:ident via $from:ident),+ $(,)?) => {$(
+            impl From<$unit> for $quantity {
+                fn from(v: $unit) -> Self {
+                    $quantity::new::<$uom_unit>(f64::from(v.0))
                 }
-                let value = match self.smc.$get(self.next) {
-                    Ok(value) => value,
-                    Err(e) => return Some(Err(e)),
-                };
-                self.next += 1;
-                Some(Ok(value))
-            }
-
-            fn size_hint(&self) -> (usize, Option<usize>) {
-                let items_left = (self.max - self.next) as usize;
-                (items_left, Some(items_left))
-            }
-
-            fn count(self) -> usize {
-                (self.max - self.next) as usize
-            }
-
-            fn last(mut self) -> Option<Self::Item> {
-                self.next = self.next.max(self.max.saturating_sub(1));
-                self.next()
-            }
-
-            fn nth(&mut self, n: usize) -> Option<Self::Item> {
-                self.next = (self.next as usize).saturating_add(n) as $range;
-                self.next()
             }
-        }
 
-        impl<'a> DoubleEndedIterator for $struct<'a> {
-            fn next_back(&mut self) -> Option<Self::Item> {
-                if self.max <= self.next {
-                    return None;
+            impl From<$quantity> for $unit {
+                fn from(v: $quantity) -> Self {
+                    $unit($from(v.get::<$uom_unit>()))
                 }
-                let value = match self.smc.$get(self.max) {
-                    Ok(value) => value,
-                    Err(e) => return Some(Err(e)),
-                };
-                self.max = self.max.saturating_sub(1);
-                Some(Ok(value))
             }
+        )+};
+    }
 
-            fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
-                self.max = (self.max as usize).saturating_sub(n) as $range;
-                self.next_back()
-            }
-        }
-    };
+    uom_conversion! {
+        Celsius = ThermodynamicTemperature in degree_celsius via temperature,
+        Fahrenheit = ThermodynamicTemperature in degree_fahrenheit via temperature,
+        Kelvin = ThermodynamicTemperature in kelvin via value,
+        Rpm = AngularVelocity in revolution_per_minute via value,
+        MilliAmpereHours = ElectricCharge in milliampere_hour via unsigned,
+        MilliAmpere = ElectricCurrent in milliampere via signed,
+        Volt = ElectricPotential in volt via value,
+        Ampere = ElectricCurrent in ampere via value,
+        Percent = Ratio in percent via value,
+        Watt = Power in watt via value,
+        WattHours = Energy in watt_hour via value,
+        Joules = Energy in joule via value,
+    }
 }
 
-iter_impl! {
-    /// Iterator for [`FanSpeed`]s.
-    FanIter(u8) = number_of_fans: fan_speed -> FanSpeed
+/// Raw data value from a sensor
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum DataValue {
+    /// true/false value
+    Flag(bool),
+    /// float value
+    Float(f32),
+    /// unsigned integer
+    Int(i64),
+    /// signed integer
+    Uint(u64),
+    /// possible a string
+    Str(String),
+    /// Any other type that could not be decoded, containing its bytes
+    Unknown(Vec<u8>),
 }
 
-iter_impl! {
-    /// Iterator for [`BatteryDetail`]s.
-    BatteryIter(u8) = number_of_batteries: battery_detail -> BatteryDetail
+/// The hardware platform that the SMC is running on.
+/// The platform decides which sensor keys are used to fill the various sensor groups.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # fn main() -> Result<()> {
+/// let smc = Smc::connect()?;
+/// match smc.platform() {
+///     Platform::Intel => println!("Intel"),
+///     Platform::AppleSilicon(generation) => println!("Apple M{}", generation),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Platform {
+    /// An Intel based Mac
+    Intel,
+    /// An Apple Silicon based Mac, with the generation of its M-series chip (e.g. 1 for M1).
+    /// The generation is 0 if it could not be determined.
+    AppleSilicon(u8),
 }
 
-#[cfg(any(doc, target_os = "macos"))]
-iter_impl! {
-    /// Iterator for the [`Celsius`] temperatures of all cpu cores.
-    CpuIter(u8) = number_of_cpus: cpu_core_temperature -> Celsius
+/// A version of the SMC firmware or interface, e.g. `2.44f6`
+///
+/// # Examples
+/// ```
+/// # use macsmc::SmcVersion;
+/// let version = SmcVersion {
+///     major: 0x02,
+///     minor: 0x44,
+///     build: 0x0f,
+///     release: 6,
+/// };
+/// assert_eq!(version.to_string(), "2.44f6");
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmcVersion {
+    /// The major version
+    pub major: u8,
+    /// The minor version
+    pub minor: u8,
+    /// The build stage, e.g. `0x0f` for a final build
+    pub build: u8,
+    /// The release number
+    pub release: u16,
 }
 
-iter_impl! {
-    /// Iterator for all [`DbgKeyInfo`]s.
-    KeysIter(u32) = number_of_keys: key_info_by_index -> DbgKeyInfo
+impl Display for SmcVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:x}.{:x}{:x}{}",
+            self.major, self.minor, self.build, self.release
+        )
+    }
 }
 
-iter_impl! {
-    /// Iterator for all [`Dbg`]s.
-    DataIter(u32) = number_of_keys: key_data_by_index -> Dbg
+/// Identification of the SMC firmware and the platform it is running on.
+/// If a key is missing, the value is empty or 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SystemInfo {
+    /// The platform that was detected, which decides which sensor keys are used.
+    pub platform: Platform,
+    /// The revision of the SMC firmware (`REV ` key)
+    pub revision: SmcVersion,
+    /// The version of the SMC interface, as reported by the SMC itself
+    pub interface_version: SmcVersion,
+    /// The branch that the SMC firmware was built from (`RBr ` key), e.g. `j680`
+    pub branch: String,
+    /// The platform identifier of the SMC firmware (`RPlt` key), e.g. `j680`
+    pub platform_id: String,
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq)]
-struct CommandKey(u32);
-
-impl Deref for CommandKey {
-    type Target = u32;
-
-    fn deref(&self) -> &Self::Target {
-        &self.0
-    }
+/// Power limits that the SMC currently applies, e.g. because of thermal pressure.
+///
+/// The limits are the raw values of the SMC, which are not documented and have no unit.
+/// Only whether they are 0 is meaningful: a limit of 0 means that the component
+/// is not being limited, any other value means that the SMC throttles it.
+/// If the SMC does not report any limits, all values are 0.
+///
+/// # Examples
+/// ```
+/// # use macsmc::PowerLimits;
+/// let limits = PowerLimits {
+///     version: 1,
+///     cpu: 0,
+///     gpu: 2,
+///     memory: 0,
+/// };
+/// assert!(!limits.cpu_limited());
+/// assert!(limits.gpu_limited());
+/// assert!(limits.is_limited());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerLimits {
+    /// The version of the limit data structure
+    pub version: u16,
+    /// The raw power limit applied to the CPU, or 0 if it is not limited
+    pub cpu: u32,
+    /// The raw power limit applied to the GPU, or 0 if it is not limited
+    pub gpu: u32,
+    /// The raw power limit applied to the memory, or 0 if it is not limited
+    pub memory: u32,
 }
 
-impl CommandKey {
-    fn set1(self, value: u8) -> Self {
-        let value = b'0' + value;
-        let mut bytes = self.0.to_be_bytes();
-        bytes[1] = value;
-        CommandKey(u32::from_be_bytes(bytes))
+impl PowerLimits {
+    /// Returns true if the CPU is being limited, e.g. due to thermal throttling.
+    pub fn cpu_limited(&self) -> bool {
+        self.cpu != 0
     }
 
-    fn set2(self, value: u8) -> Self {
-        let value = b'0' + value;
-        let mut bytes = self.0.to_be_bytes();
-        bytes[2] = value;
-        CommandKey(u32::from_be_bytes(bytes))
+    /// Returns true if the GPU is being limited, e.g. due to thermal throttling.
+    pub fn gpu_limited(&self) -> bool {
+        self.gpu != 0
     }
-}
 
-trait ReadAction {
-    type Out: ValueParser;
-
-    fn key(&self) -> CommandKey;
+    /// Returns true if the memory is being limited.
+    pub fn memory_limited(&self) -> bool {
+        self.memory != 0
+    }
 
-    fn parse(self, val: DataValue) -> InternalResult<Self::Out>
-    where
-        Self: Sized,
-    {
-        <Self::Out as ValueParser>::parse(val)
+    /// Returns true if any component is being limited.
+    pub fn is_limited(&self) -> bool {
+        self.cpu_limited() || self.gpu_limited() || self.memory_limited()
     }
 }
 
-trait ValueParser: Sized {
-    fn parse(val: DataValue) -> InternalResult<Self>;
+/// How hot the machine is overall, from the coolest to the hottest state.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ThermalState {
+    /// All sensors are within their normal range
+    Nominal,
+    /// Some sensors are warm or the fans are spinning faster, but nothing needs attention
+    Fair,
+    /// Some sensors are hot, the fans are close to their maximum, or the SMC throttles a component
+    Serious,
+    /// Some sensors are above their critical thresholds
+    Critical,
 }
 
-impl ValueParser for Celsius {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Float(value) => Ok(Self(value)),
-            _ => Err(InternalError::_DataValueError),
-        }
+impl Display for ThermalState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            ThermalState::Nominal => "Nominal",
+            ThermalState::Fair => "Fair",
+            ThermalState::Serious => "Serious",
+            ThermalState::Critical => "Critical",
+        })
     }
 }
 
-impl ValueParser for Rpm {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Float(value) => Ok(Self(value)),
-            DataValue::Uint(v) => Ok(Self(f32::from(u16::try_from(v)?))),
-            _ => Err(InternalError::_DataValueError),
-        }
-    }
+/// The reading that decided the [`ThermalState`] of a [`ThermalStatus`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ThermalCause {
+    /// A temperature sensor, with the id of its [`CatalogEntry`]
+    Temperature {
+        /// The id of the sensor, e.g. `cpu.die`
+        id: String,
+        /// The temperature of the sensor
+        temperature: Celsius,
+    },
+    /// A fan, with its speed as percentage of its max speed
+    Fan {
+        /// The index of the fan, starting at 0
+        index: usize,
+        /// The speed of the fan, see [`FanSpeed::percentage`]
+        percentage: f32,
+    },
+    /// The SMC limits the power of some components
+    PowerLimit(PowerLimits),
 }
 
-impl ValueParser for FanMode {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Flag(bool) => Ok(bool.into()),
-            DataValue::Int(value) => Ok((value != 0).into()),
-            DataValue::Uint(value) => Ok((value != 0).into()),
-            DataValue::Float(value) => Ok((value != 0.0).into()),
-            _ => Err(InternalError::_DataValueError),
+/// The overall thermal state of the machine, and the reading that decided it.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// let cpu = CpuTemperatures {
+///     proximity: Celsius(60.0),
+///     die: Celsius(97.0),
+///     ..CpuTemperatures::default()
+/// };
+/// let status = ThermalStatus::classify(
+///     &cpu,
+///     &GpuTemperatures::default(),
+///     &[],
+///     &PowerLimits::default(),
+///     |id| DefaultThresholds.temperature(id, Platform::Intel, ""),
+/// );
+/// assert_eq!(status.state, ThermalState::Critical);
+/// assert_eq!(
+///     status.cause,
+///     Some(ThermalCause::Temperature {
+///         id: "cpu.die".into(),
+///         temperature: Celsius(97.0)
+///     })
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThermalStatus {
+    /// The overall state, which is the state of the hottest reading
+    pub state: ThermalState,
+    /// The reading that decided the state, or None if the state is nominal
+    pub cause: Option<ThermalCause>,
+}
+
+impl ThermalStatus {
+    /// Folds the temperatures, fan speeds and power limits into one [`ThermalState`].
+    ///
+    /// Temperatures are compared against the thresholds that are returned for the id
+    /// of the sensor, e.g. by [`Smc::temperature_thresholds`]. A temperature above the
+    /// second threshold is fair, above the third is serious, and above the last one is critical.
+    /// Fans are fair above 2/3 and serious above 95% of their max speed.
+    /// Any power limit is serious, as the SMC is throttling the machine.
+    /// Missing sensors are ignored. If several readings are equally hot, the first one
+    /// in the order of the arguments is reported.
+    pub fn classify(
+        cpu: &CpuTemperatures,
+        gpu: &GpuTemperatures,
+        fans: &[FanSpeed],
+        limits: &PowerLimits,
+        thresholds: impl Fn(&str) -> [Celsius; 4],
+    ) -> Self {
+        let temperatures = [
+            ("cpu.proximity", cpu.proximity),
+            ("cpu.die", cpu.die),
+            ("cpu.graphics", cpu.graphics),
+            ("cpu.system_agent", cpu.system_agent),
+            ("gpu.proximity", gpu.proximity),
+            ("gpu.die", gpu.die),
+        ];
+
+        let mut status = ThermalStatus {
+            state: ThermalState::Nominal,
+            cause: None,
+        };
+        for &(id, temperature) in temperatures.iter().filter(|(_, t)| **t > 0.0) {
+            let thresholds = thresholds(id);
+            let state = if temperature > thresholds[3] {
+                ThermalState::Critical
+            } else if temperature > thresholds[2] {
+                ThermalState::Serious
+            } else if temperature > thresholds[1] {
+                ThermalState::Fair
+            } else {
+                ThermalState::Nominal
+            };
+            status.raise(state, || ThermalCause::Temperature {
+                id: id.to_string(),
+                temperature,
+            });
+        }
+        for (index, fan) in fans.iter().enumerate() {
+            let percentage = fan.percentage();
+            let state = if !percentage.is_finite() {
+                continue;
+            } else if percentage > 95.0 {
+                ThermalState::Serious
+            } else if percentage > 200.0 / 3.0 {
+                ThermalState::Fair
+            } else {
+                ThermalState::Nominal
+            };
+            status.raise(state, || ThermalCause::Fan { index, percentage });
+        }
+        if limits.is_limited() {
+            status.raise(ThermalState::Serious, || ThermalCause::PowerLimit(*limits));
+        }
+        status
+    }
+
+    fn raise(&mut self, state: ThermalState, cause: impl FnOnce() -> ThermalCause) {
+        if state > self.state {
+            self.state = state;
+            self.cause = Some(cause());
         }
     }
 }
 
-#[derive(Debug, Default)]
-struct BatteryStatus {
-    charging: bool,
-    ac_present: bool,
-    health_ok: bool,
+/// All readings of the SMC, taken at one instant by [`Smc::snapshot`].
+///
+/// A snapshot is plain data, so it can be kept around to compare readings over time
+/// or to export them. It can also be built without an SMC, e.g. for tests.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # use std::time::UNIX_EPOCH;
+/// let system_info = SystemInfo {
+///     platform: Platform::Intel,
+///     revision: SmcVersion::default(),
+///     interface_version: SmcVersion::default(),
+///     branch: String::from("j680"),
+///     platform_id: String::from("j680"),
+/// };
+/// let snapshot = SmcSnapshot {
+///     power_system_total: Watt(24.0),
+///     ..SmcSnapshot::new(UNIX_EPOCH, system_info)
+/// };
+/// assert!(snapshot.fans.is_empty());
+/// assert_eq!(*snapshot.power_system_total, 24.0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SmcSnapshot {
+    /// The time at which the readings were started
+    pub timestamp: SystemTime,
+    /// See [`Smc::system_info`]
+    pub system_info: SystemInfo,
+    /// See [`Smc::cpu_temperature`]
+    pub cpu_temperature: CpuTemperatures,
+    /// See [`Smc::cpu_core_temps`]
+    pub cpu_cores: Vec<CoreTemperature>,
+    /// See [`Smc::gpu_temperature`]
+    pub gpu_temperature: GpuTemperatures,
+    /// See [`Smc::gpus`]
+    pub gpus: Vec<GpuDetail>,
+    /// See [`Smc::other_temperatures`]
+    pub other_temperatures: OtherTemperatures,
+    /// See [`Smc::dimm_temperatures`]
+    pub dimm_temperatures: Vec<DimmTemperatures>,
+    /// See [`Smc::drive_temperatures`]
+    pub drive_temperatures: Vec<DriveTemperatures>,
+    /// See [`Smc::fans`]
+    pub fans: Vec<FanSpeed>,
+    /// See [`Smc::battery_info`]
+    pub battery_info: BatteryInfo,
+    /// See [`Smc::battery_details`]
+    pub batteries: Vec<BatteryDetail>,
+    /// See [`Smc::cpu_power`]
+    pub cpu_power: CpuPower,
+    /// See [`Smc::gpu_power`]
+    pub gpu_power: Watt,
+    /// See [`Smc::power_dc_in`]
+    pub power_dc_in: Watt,
+    /// See [`Smc::power_system_total`]
+    pub power_system_total: Watt,
+    /// See [`Smc::power_limits`]
+    pub power_limits: PowerLimits,
+    /// See [`Smc::voltages`]
+    pub voltages: Voltages,
+    /// See [`Smc::currents`]
+    pub currents: Currents,
+    /// See [`Smc::ambient_light`]
+    pub ambient_light: AmbientLight,
+    /// See [`Smc::is_lid_closed`]
+    pub lid_closed: bool,
+    /// See [`Smc::motion`]
+    pub motion: Motion,
+    /// See [`Smc::keyboard_backlight`]
+    pub keyboard_backlight: Percent,
 }
 
-impl ValueParser for BatteryStatus {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Uint(val) => {
-                let charging = val & 0x01 == 0x01;
-                let ac_present = val & 0x02 == 0x02;
-                let health_ok = val & 0x40 == 0x40;
-                Ok(BatteryStatus {
-                    charging,
-                    ac_present,
-                    health_ok,
-                })
-            }
-            _ => Err(InternalError::_DataValueError),
+impl SmcSnapshot {
+    /// Creates a snapshot without any readings, all values are 0.0 and all lists are empty.
+    pub fn new(timestamp: SystemTime, system_info: SystemInfo) -> Self {
+        Self {
+            timestamp,
+            system_info,
+            cpu_temperature: CpuTemperatures::default(),
+            cpu_cores: Vec::new(),
+            gpu_temperature: GpuTemperatures::default(),
+            gpus: Vec::new(),
+            other_temperatures: OtherTemperatures::default(),
+            dimm_temperatures: Vec::new(),
+            drive_temperatures: Vec::new(),
+            fans: Vec::new(),
+            battery_info: BatteryInfo::default(),
+            batteries: Vec::new(),
+            cpu_power: CpuPower::default(),
+            gpu_power: Watt::default(),
+            power_dc_in: Watt::default(),
+            power_system_total: Watt::default(),
+            power_limits: PowerLimits::default(),
+            voltages: Voltages::default(),
+            currents: Currents::default(),
+            ambient_light: AmbientLight::default(),
+            lid_closed: false,
+            motion: Motion::default(),
+            keyboard_backlight: Percent::default(),
         }
     }
+
+    /// Folds the readings of this snapshot into a [`ThermalStatus`],
+    /// see [`ThermalStatus::classify`].
+    pub fn thermal_status(&self, thresholds: impl Fn(&str) -> [Celsius; 4]) -> ThermalStatus {
+        ThermalStatus::classify(
+            &self.cpu_temperature,
+            &self.gpu_temperature,
+            &self.fans,
+            &self.power_limits,
+            thresholds,
+        )
+    }
 }
 
-impl ValueParser for MilliAmpereHours {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Uint(v) => Ok(Self(v.try_into()?)),
-            _ => Err(InternalError::_DataValueError),
+/// A group of sensors that is read into an [`SmcSnapshot`], see [`Smc::snapshot_of`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum SensorGroup {
+    /// [`SmcSnapshot::cpu_temperature`]
+    Cpu,
+    /// [`SmcSnapshot::cpu_cores`]
+    CpuCores,
+    /// [`SmcSnapshot::gpu_temperature`] and [`SmcSnapshot::gpus`]
+    Gpu,
+    /// [`SmcSnapshot::other_temperatures`], [`SmcSnapshot::dimm_temperatures`]
+    /// and [`SmcSnapshot::drive_temperatures`]
+    Other,
+    /// [`SmcSnapshot::fans`]
+    Fans,
+    /// [`SmcSnapshot::battery_info`] and [`SmcSnapshot::batteries`]
+    Battery,
+    /// [`SmcSnapshot::cpu_power`], [`SmcSnapshot::gpu_power`], [`SmcSnapshot::power_dc_in`],
+    /// [`SmcSnapshot::power_system_total`] and [`SmcSnapshot::power_limits`]
+    Power,
+    /// [`SmcSnapshot::voltages`]
+    Voltages,
+    /// [`SmcSnapshot::currents`]
+    Currents,
+    /// [`SmcSnapshot::ambient_light`], [`SmcSnapshot::lid_closed`], [`SmcSnapshot::motion`]
+    /// and [`SmcSnapshot::keyboard_backlight`]
+    Environment,
+}
+
+impl SensorGroup {
+    /// All groups, which together fill every field of an [`SmcSnapshot`].
+    pub const ALL: [Self; 10] = [
+        SensorGroup::Cpu,
+        SensorGroup::CpuCores,
+        SensorGroup::Gpu,
+        SensorGroup::Other,
+        SensorGroup::Fans,
+        SensorGroup::Battery,
+        SensorGroup::Power,
+        SensorGroup::Voltages,
+        SensorGroup::Currents,
+        SensorGroup::Environment,
+    ];
+}
+
+/// The category of a [`CatalogEntry`], matching the sections of the `macstats` output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Category {
+    /// CPU temperatures
+    Cpu,
+    /// GPU temperatures
+    Gpu,
+    /// Other temperatures, e.g. memory, drives, or the mainboard
+    Other,
+    /// Fan speeds
+    Fan,
+    /// Battery information
+    Battery,
+    /// Power consumption
+    Power,
+    /// Voltages of the power rails
+    Voltage,
+    /// Currents on the power rails
+    Current,
+    /// Environment sensors, e.g. ambient light, lid state or motion
+    Environment,
+    /// Identification of the SMC firmware
+    System,
+}
+
+impl Category {
+    /// Returns the name of the category as it is used in the catalog.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Category;
+    /// assert_eq!(Category::Environment.name(), "env");
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Category::Cpu => "cpu",
+            Category::Gpu => "gpu",
+            Category::Other => "other",
+            Category::Fan => "fan",
+            Category::Battery => "battery",
+            Category::Power => "power",
+            Category::Voltage => "voltage",
+            Category::Current => "current",
+            Category::Environment => "env",
+            Category::System => "system",
         }
     }
+
+    const ALL: [Self; 10] = [
+        Category::Cpu,
+        Category::Gpu,
+        Category::Other,
+        Category::Fan,
+        Category::Battery,
+        Category::Power,
+        Category::Voltage,
+        Category::Current,
+        Category::Environment,
+        Category::System,
+    ];
 }
 
-impl ValueParser for MilliAmpere {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Int(v) => Ok(Self(v.try_into()?)),
-            _ => Err(InternalError::_DataValueError),
+/// The unit of the value of a [`CatalogEntry`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Unit {
+    /// Degrees [`Celsius`]
+    Celsius,
+    /// Revolutions per minute, see [`Rpm`]
+    Rpm,
+    /// [`Watt`]
+    Watt,
+    /// [`Volt`]
+    Volt,
+    /// [`Ampere`]
+    Ampere,
+    /// [`MilliAmpere`]
+    MilliAmpere,
+    /// [`MilliAmpereHours`]
+    MilliAmpereHours,
+    /// [`Percent`]
+    Percent,
+    /// The value has no unit, e.g. a flag or a count
+    None,
+}
+
+impl Unit {
+    /// Returns the symbol of the unit as it is used in the catalog.
+    /// The symbol of [`Unit::None`] is `-`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Unit;
+    /// assert_eq!(Unit::Celsius.symbol(), "°C");
+    /// ```
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Unit::Celsius => "°C",
+            Unit::Rpm => "RPM",
+            Unit::Watt => "W",
+            Unit::Volt => "V",
+            Unit::Ampere => "A",
+            Unit::MilliAmpere => "mA",
+            Unit::MilliAmpereHours => "mAh",
+            Unit::Percent => "%",
+            Unit::None => "-",
         }
     }
-}
 
-impl ValueParser for Watt {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Float(v) => Ok(Self(v)),
-            _ => Err(InternalError::_DataValueError),
+    /// Returns the range of values that a working sensor in this unit can report.
+    /// Values outside of this range come from a broken or missing sensor,
+    /// e.g. -127 °C, 0 °C or 255 °C.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Unit;
+    /// assert!(Unit::Celsius.is_plausible(42.0));
+    /// assert!(!Unit::Celsius.is_plausible(0.0));
+    /// assert!(!Unit::Celsius.is_plausible(-127.0));
+    /// assert!(!Unit::Percent.is_plausible(255.0));
+    /// ```
+    pub fn plausible_range(self) -> RangeInclusive<f32> {
+        match self {
+            Unit::Celsius => 1.0..=130.0,
+            Unit::Rpm => 0.0..=10_000.0,
+            Unit::Watt => -500.0..=500.0,
+            Unit::Volt => 0.0..=30.0,
+            Unit::Ampere => -20.0..=20.0,
+            Unit::MilliAmpere => -20_000.0..=20_000.0,
+            Unit::MilliAmpereHours => 0.0..=30_000.0,
+            Unit::Percent => 0.0..=100.0,
+            Unit::None => f32::NEG_INFINITY..=f32::INFINITY,
         }
     }
-}
 
-impl ValueParser for Volt {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Float(v) => Ok(Self(v)),
-            DataValue::Uint(v) => Ok(Self(f32::from(u16::try_from(v)?) / 1000.0)),
-            _ => Err(InternalError::_DataValueError),
+    /// Returns true if the value is within the [`Unit::plausible_range`].
+    pub fn is_plausible(self, value: f32) -> bool {
+        self.plausible_range().contains(&value)
+    }
+
+    /// Returns true if a working sensor in this unit never reports the exact same value
+    /// for a long time. Fan speeds, capacities or percentages can stay the same for hours.
+    fn fluctuates(self) -> bool {
+        match self {
+            Unit::Celsius | Unit::Watt | Unit::Volt | Unit::Ampere | Unit::MilliAmpere => true,
+            Unit::Rpm | Unit::MilliAmpereHours | Unit::Percent | Unit::None => false,
         }
     }
+
+    const ALL: [Self; 9] = [
+        Unit::Celsius,
+        Unit::Rpm,
+        Unit::Watt,
+        Unit::Volt,
+        Unit::Ampere,
+        Unit::MilliAmpere,
+        Unit::MilliAmpereHours,
+        Unit::Percent,
+        Unit::None,
+    ];
+
+    /// Reads a key as a number in this unit.
+    /// Returns `None` if the key is missing or its value is not a number, e.g. a string.
+    fn read(self, backend: &mut Backend, key: CommandKey) -> InternalResult<Option<f32>> {
+        let value = match self {
+            Unit::Celsius => backend.read_sensor::<Celsius>(key)?.map(|v| *v),
+            Unit::Rpm => backend.read_sensor::<Rpm>(key)?.map(|v| *v),
+            Unit::Watt => backend.read_sensor::<Watt>(key)?.map(|v| *v),
+            Unit::Volt => backend.read_sensor::<Volt>(key)?.map(|v| *v),
+            Unit::Ampere => backend.read_sensor::<Ampere>(key)?.map(|v| *v),
+            Unit::MilliAmpere => backend.read_sensor::<MilliAmpere>(key)?.map(|v| *v as f32),
+            Unit::MilliAmpereHours => backend
+                .read_sensor::<MilliAmpereHours>(key)?
+                .map(|v| *v as f32),
+            Unit::Percent => backend.read_sensor::<Percent>(key)?.map(|v| *v),
+            Unit::None => match backend.read_sensor::<DataValue>(key)? {
+                Some(DataValue::Flag(v)) => Some(f32::from(u8::from(v))),
+                Some(DataValue::Float(v)) => Some(v),
+                Some(DataValue::Int(v)) => Some(v as f32),
+                Some(DataValue::Uint(v)) => Some(v as f32),
+                _ => None,
+            },
+        };
+        Ok(value)
+    }
 }
 
-impl ValueParser for bool {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Flag(v) => Ok(v),
-            _ => Err(InternalError::_DataValueError),
+/// A single key in the [`Catalog`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CatalogEntry {
+    /// The 4 character key, a `#` is a placeholder for the index of a fan, battery, etc.
+    /// A `+` is a placeholder for keys where the index starts at 1.
+    /// A `-` marks a value without a key of its own, see [`CatalogEntry::is_derived`].
+    pub key: String,
+    /// The identifier that the typed getters use to look up the key
+    pub id: String,
+    /// The category of the key
+    pub category: Category,
+    /// The unit of the value
+    pub unit: Unit,
+    /// A human readable label, a `#` is a placeholder for the number of a fan, battery, etc.
+    pub label: String,
+    /// The models that have this key, or empty if all models have it
+    pub models: Vec<String>,
+    /// A description of what the key measures
+    pub description: String,
+}
+
+impl CatalogEntry {
+    /// Returns true if this key is available on the given platform and model identifier,
+    /// e.g. `MacBookPro15,1`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::{Catalog, Platform};
+    /// let catalog = Catalog::parse("Tp09 | cpu.core.efficiency | cpu | °C | E-Core # | m1 |")?;
+    /// let entry = &catalog.entries()[0];
+    /// assert!(entry.applies_to(Platform::AppleSilicon(1), "MacBookAir10,1"));
+    /// assert!(!entry.applies_to(Platform::AppleSilicon(2), "Mac14,2"));
+    /// assert!(!entry.applies_to(Platform::Intel, "MacBookPro15,1"));
+    /// # Ok::<(), macsmc::CatalogError>(())
+    /// ```
+    pub fn applies_to(&self, platform: Platform, model: &str) -> bool {
+        self.models.is_empty()
+            || self
+                .models
+                .iter()
+                .any(|token| model_matches(token, platform, model))
+    }
+
+    /// Returns the label with the placeholder replaced by the given number.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Catalog;
+    /// let catalog = Catalog::parse("F#Ac | fan.actual | fan | RPM | Fan # Speed | |")?;
+    /// assert_eq!(catalog.entries()[0].numbered_label(2), "Fan 2 Speed");
+    /// # Ok::<(), macsmc::CatalogError>(())
+    /// ```
+    pub fn numbered_label(&self, number: usize) -> String {
+        self.label.replace('#', &number.to_string())
+    }
+
+    /// Returns true if the given key is described by this entry.
+    /// The placeholder matches any digit.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Catalog;
+    /// let catalog = Catalog::parse("F#Ac | fan.actual | fan | RPM | Fan # Speed | |")?;
+    /// assert!(catalog.entries()[0].matches_key("F1Ac"));
+    /// assert!(!catalog.entries()[0].matches_key("F1Mx"));
+    /// # Ok::<(), macsmc::CatalogError>(())
+    /// ```
+    pub fn matches_key(&self, key: &str) -> bool {
+        self.index_of(key).is_some()
+    }
+
+    /// Returns true if this entry labels a value that macsmc derives from other keys
+    /// or SMC calls, e.g. the health of a fan. Such entries do not describe any key.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::Catalog;
+    /// let catalog = Catalog::parse("- | fan.health | fan | - | Fan # Health | |")?;
+    /// assert!(catalog.entries()[0].is_derived());
+    /// assert!(!catalog.entries()[0].matches_key("-   "));
+    /// # Ok::<(), macsmc::CatalogError>(())
+    /// ```
+    pub fn is_derived(&self) -> bool {
+        self.key.trim_end() == "-"
+    }
+
+    fn is_indexed(&self) -> bool {
+        self.key.contains(&['#', '+'][..])
+    }
+
+    /// Returns the index that the given key has for this entry,
+    /// or `None` if the key is not described by this entry.
+    fn index_of(&self, key: &str) -> Option<u8> {
+        if key.len() != self.key.len() || self.is_derived() {
+            return None;
+        }
+        let mut index = 0;
+        for (pattern, c) in self.key.bytes().zip(key.bytes()) {
+            match pattern {
+                b'#' if c.is_ascii_digit() => index = c - b'0',
+                b'+' if (b'1'..=b'9').contains(&c) => index = c - b'1',
+                _ if pattern == c => {}
+                _ => return None,
+            }
         }
+        Some(index)
     }
-}
 
-impl ValueParser for u8 {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Uint(v) => Ok(u8::try_from(v)?),
-            _ => Err(InternalError::_DataValueError),
+    /// Returns the key with the placeholder replaced by the given index.
+    /// Keys without a placeholder only describe the first index.
+    fn command_key(&self, index: u8) -> Option<CommandKey> {
+        if self.is_derived() {
+            return None;
+        }
+        let mut key: [u8; 4] = self.key.as_bytes().try_into().ok()?;
+        match key.iter().position(|&c| c == b'#' || c == b'+') {
+            Some(placeholder) => {
+                let first = if key[placeholder] == b'#' { b'0' } else { b'1' };
+                key[placeholder] = first.checked_add(index).filter(u8::is_ascii_digit)?;
+            }
+            None if index != 0 => return None,
+            None => {}
         }
+        Some(CommandKey(u32::from_be_bytes(key)))
     }
-}
 
-impl ValueParser for u32 {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        match val {
-            DataValue::Uint(v) => Ok(u32::try_from(v)?),
-            _ => Err(InternalError::_DataValueError),
+    fn parse(line: &str) -> std::result::Result<Self, &'static str> {
+        let mut columns = line.split('|').map(str::trim);
+        let mut column = |name| columns.next().ok_or(name);
+
+        let key = column("missing key")?;
+        if key.is_empty() || key.len() > 4 || !key.is_ascii() {
+            return Err("the key must have 1 to 4 ASCII characters");
+        }
+        if key.matches(&['#', '+'][..]).count() > 1 {
+            return Err("the key must have at most one placeholder");
+        }
+        let key = format!("{:4}", key);
+
+        let id = column("missing id")?;
+        if id.is_empty() {
+            return Err("the id must not be empty");
+        }
+
+        let category = column("missing category")?;
+        let category = Category::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name() == category)
+            .ok_or("unknown category")?;
+
+        let unit = column("missing unit")?;
+        let unit = Unit::ALL
+            .iter()
+            .copied()
+            .find(|u| u.symbol() == unit)
+            .ok_or("unknown unit")?;
+
+        let label = column("missing label")?;
+        let models = column("missing models")?
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        let description = column("missing description")?;
+
+        if columns.next().is_some() {
+            return Err("too many columns");
         }
+
+        Ok(CatalogEntry {
+            key,
+            id: id.to_string(),
+            category,
+            unit,
+            label: label.to_string(),
+            models,
+            description: description.to_string(),
+        })
     }
 }
 
-impl ValueParser for DataValue {
-    fn parse(val: DataValue) -> InternalResult<Self> {
-        Ok(val)
+fn model_matches(token: &str, platform: Platform, model: &str) -> bool {
+    match (token, platform) {
+        ("intel", Platform::Intel) | ("apple", Platform::AppleSilicon(_)) => return true,
+        ("intel", _) | ("apple", _) => return false,
+        _ => {}
+    }
+    let generation = token.strip_prefix('m').and_then(|generation| {
+        let (generation, or_later) = match generation.strip_suffix('+') {
+            Some(generation) => (generation, true),
+            None => (generation, false),
+        };
+        Some((generation.parse::<u8>().ok()?, or_later))
+    });
+    match (generation, platform) {
+        // An unknown generation is treated as the first one
+        (Some((min, or_later)), Platform::AppleSilicon(generation)) => {
+            let generation = generation.max(1);
+            generation == min || (or_later && generation > min)
+        }
+        (Some(_), Platform::Intel) => false,
+        // `MacBookPro1` must not match `MacBookPro15,1`
+        (None, _) => {
+            let family = model.split(',').next().unwrap_or_default();
+            !model.is_empty() && (model == token || family == token)
+        }
     }
 }
 
-struct Check<'a>(&'a str);
+/// The catalog of SMC keys that macsmc knows about.
+/// It maps every key to a label, category, unit, description and the models that have the key.
+///
+/// The typed getters of [`Smc`] look up their keys in the catalog by id,
+/// so the catalog can be extended to support new models without changing the code.
+/// See the [built-in catalog] for the format and the available ids.
+///
+/// [built-in catalog]: https://github.com/knutwalker/macstats/blob/main/macsmc/src/catalog.txt
+///
+/// # Examples
+/// ```
+/// # use macsmc::{Catalog, Platform};
+/// let mut catalog = Catalog::builtin();
+/// assert_eq!(catalog.label("cpu.proximity"), Some("CPU Proximity"));
+///
+/// let custom = Catalog::parse(
+///     "TC0P | cpu.proximity | cpu | °C | CPU Heatsink | MacBookPro15 | Relabeled for this model",
+/// )?;
+/// catalog.extend(custom);
+/// let label = |model| catalog.label_for("cpu.proximity", Platform::Intel, model);
+/// assert_eq!(label("MacBookPro15,1"), Some("CPU Heatsink"));
+/// assert_eq!(label("MacBookPro16,1"), Some("CPU Proximity"));
+/// # Ok::<(), macsmc::CatalogError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Catalog {
+    entries: Vec<CatalogEntry>,
+}
 
-impl<'a> ReadAction for Check<'a> {
-    type Out = DataValue;
+static BUILTIN_CATALOG: &str = include_str!("catalog.txt");
 
-    fn key(&self) -> CommandKey {
-        let bytes = self.0.as_bytes();
-        let key = u32::from_be_bytes(bytes.try_into().unwrap());
-        CommandKey(key)
+impl Catalog {
+    /// Returns the catalog that is embedded in this crate.
+    pub fn builtin() -> Self {
+        Self::parse(BUILTIN_CATALOG).expect("the built-in catalog is invalid")
     }
-}
-
-#[derive(Clone, Debug, PartialEq)]
-struct DataType(DataValue, u32);
 
-macro_rules! read_impl {
-    ($struct:ident = $key:ident -> $out:tt) => {
-        #[derive(Debug)]
-        struct $struct;
+    /// Parses a catalog in the format of the [built-in catalog](Catalog).
+    ///
+    /// # Errors
+    /// [`CatalogError::InvalidEntry`] If a line could not be parsed
+    pub fn parse(catalog: &str) -> std::result::Result<Self, CatalogError> {
+        let entries = catalog
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with('#')
+            })
+            .map(|(number, line)| {
+                CatalogEntry::parse(line).map_err(|reason| CatalogError::InvalidEntry {
+                    line: number + 1,
+                    reason,
+                })
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Catalog { entries })
+    }
 
-        impl $crate::ReadAction for $struct {
-            type Out = $out;
+    /// Reads and parses a catalog file, e.g. to [extend](Catalog::extend) the built-in catalog.
+    ///
+    /// # Errors
+    /// [`CatalogError::Io`] If the file could not be read
+    ///
+    /// [`CatalogError::InvalidEntry`] If a line could not be parsed
+    pub fn load(path: impl AsRef<Path>) -> std::result::Result<Self, CatalogError> {
+        let catalog = fs::read_to_string(path).map_err(CatalogError::Io)?;
+        Self::parse(&catalog)
+    }
 
-            fn key(&self) -> CommandKey {
-                $key
+    /// Adds all entries of the other catalog to this one.
+    /// An entry replaces an existing entry with the same key, id and models.
+    /// Other entries take precedence over the existing entries with the same id.
+    pub fn extend(&mut self, other: Catalog) {
+        for entry in other.entries {
+            let same = |existing: &CatalogEntry| {
+                existing.key == entry.key
+                    && existing.id == entry.id
+                    && existing.models == entry.models
+            };
+            if let Some(existing) = self.entries.iter_mut().find(|existing| same(existing)) {
+                *existing = entry;
+            } else if let Some(position) = self.entries.iter().position(|e| e.id == entry.id) {
+                self.entries.insert(position, entry);
+            } else {
+                self.entries.push(entry);
             }
         }
-    };
+    }
 
-    ($struct:ident($arg:tt) = $key:ident -> $out:tt) => {
-        #[derive(Debug)]
-        struct $struct($arg);
+    /// Returns all entries of the catalog.
+    pub fn entries(&self) -> &[CatalogEntry] {
+        &self.entries
+    }
 
-        impl $crate::ReadAction for $struct {
-            type Out = $out;
+    /// Returns all entries with the given id, in the order that they are tried.
+    pub fn entries_by_id<'a>(&'a self, id: &'a str) -> impl Iterator<Item = &'a CatalogEntry> {
+        self.entries.iter().filter(move |entry| entry.id == id)
+    }
 
-            fn key(&self) -> CommandKey {
-                $key.set1(self.0)
-            }
-        }
-    };
+    /// Returns the first entry that describes the given key and is available
+    /// on the given platform and model identifier.
+    pub fn entry_for_key(
+        &self,
+        key: &str,
+        platform: Platform,
+        model: &str,
+    ) -> Option<&CatalogEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.matches_key(key) && entry.applies_to(platform, model))
+    }
 
-    ($struct:ident($arg:tt) == $key:ident -> $out:tt) => {
-        #[derive(Debug)]
-        struct $struct($arg);
+    /// Returns the label of the first entry with the given id, regardless of the models
+    /// that the entry applies to. Use [`Catalog::label_for`] to label the sensors of a machine.
+    pub fn label(&self, id: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .map(|entry| entry.label.as_str())
+    }
+
+    /// Returns the label of the given id on the given platform and model identifier.
+    /// This is the label of the first entry with the id that applies to the machine,
+    /// or of the first entry with the id if none of them applies.
+    pub fn label_for(&self, id: &str, platform: Platform, model: &str) -> Option<&str> {
+        let mut entries = self.entries.iter().filter(|entry| entry.id == id);
+        let first = entries.clone().next();
+        entries
+            .find(|entry| entry.applies_to(platform, model))
+            .or(first)
+            .map(|entry| entry.label.as_str())
+    }
+}
+
+/// Errors that can happen when parsing a [`Catalog`]
+#[derive(Debug)]
+pub enum CatalogError {
+    /// The catalog file could not be read
+    Io(io::Error),
+    /// A line of the catalog could not be parsed
+    InvalidEntry {
+        /// The line number, starting at 1
+        line: usize,
+        /// Why the line could not be parsed
+        reason: &'static str,
+    },
+}
 
-        impl $crate::ReadAction for $struct {
-            type Out = $out;
+impl StdError for CatalogError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            CatalogError::Io(e) => Some(e),
+            CatalogError::InvalidEntry { .. } => None,
+        }
+    }
+}
 
-            fn key(&self) -> CommandKey {
-                $key.set2(self.0)
+impl Display for CatalogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CatalogError::Io(e) => write!(f, "Could not read the catalog: {}", e),
+            CatalogError::InvalidEntry { line, reason } => {
+                write!(f, "Invalid catalog entry in line {}: {}", line, reason)
             }
         }
-    };
+    }
 }
 
-static NUMBER_OF_KEYS: CommandKey = smc_key(b"#KEY");
+/// The value of a sensor that was found by [`Smc::discover`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Reading {
+    /// A temperature sensor, the key starts with `T`
+    Temperature(Celsius),
+    /// The current speed of a fan, the key has the form `F#Ac`
+    Fan(Rpm),
+    /// A power sensor, the key starts with `P`
+    Power(Watt),
+    /// A voltage sensor, the key starts with `V`
+    Voltage(Volt),
+    /// A current sensor, the key starts with `I`
+    Current(Ampere),
+}
+
+impl Reading {
+    /// Returns the raw value, without the unit.
+    pub fn value(self) -> f32 {
+        match self {
+            Reading::Temperature(value) => *value,
+            Reading::Fan(value) => *value,
+            Reading::Power(value) => *value,
+            Reading::Voltage(value) => *value,
+            Reading::Current(value) => *value,
+        }
+    }
 
-static NUM_FANS: CommandKey = smc_key(b"FNum");
-static FAN_MODE: CommandKey = smc_key(b"F0Md");
-static FAN_SPEED_ACTUAL: CommandKey = smc_key(b"F0Ac");
-static FAN_SPEED_MAX: CommandKey = smc_key(b"F0Mx");
-static FAN_SPEED_MIN: CommandKey = smc_key(b"F0Mn");
-static FAN_SPEED_SAFE: CommandKey = smc_key(b"F0Sf");
-static FAN_SPEED_TARGET: CommandKey = smc_key(b"F0Tg");
-
-static NUM_BATTERIES: CommandKey = smc_key(b"BNum");
-static BATTERY_POWERED: CommandKey = smc_key(b"BATP");
-static BATTERY_INFO: CommandKey = smc_key(b"BSIn");
-static BATTERY_CYCLES: CommandKey = smc_key(b"B0CT");
-static BATTERY_CURRENT_CAPACITY: CommandKey = smc_key(b"B0RM");
-static BATTERY_FULL_CAPACITY: CommandKey = smc_key(b"B0FC");
-static BATTERY_POWER: CommandKey = smc_key(b"B0AP");
-static BATTERY_AMPERAGE: CommandKey = smc_key(b"B0AC");
-static BATTERY_VOLTAGE: CommandKey = smc_key(b"B0AV");
-
-static TEMP_BATTERY_MAX: CommandKey = smc_key(b"TB0T");
-static TEMP_BATTERY_1: CommandKey = smc_key(b"TB1T");
-static TEMP_BATTERY_2: CommandKey = smc_key(b"TB2T");
-
-static TEMP_CPU_CORE: CommandKey = smc_key(b"TC0C");
-static TEMP_CPU_DIE: CommandKey = smc_key(b"TC0F");
-static TEMP_CPU_SYSTEM_AGENT: CommandKey = smc_key(b"TCSA");
-static TEMP_CPU_GFX: CommandKey = smc_key(b"TCGC");
-static TEMP_CPU_PROXIMITY: CommandKey = smc_key(b"TC0P");
-
-static TEMP_GPU_PROXIMITY: CommandKey = smc_key(b"TG0P");
-static TEMP_GPU_DIE: CommandKey = smc_key(b"TGDD");
-
-static TEMP_MEM_PROXIMITY: CommandKey = smc_key(b"TM0P");
-static TEMP_PLATFORM_CONTROLLER_HUB_DIE: CommandKey = smc_key(b"TPCD");
-static TEMP_HEATPIPE_1: CommandKey = smc_key(b"Th1H");
-static TEMP_HEATPIPE_2: CommandKey = smc_key(b"Th2H");
-static TEMP_MAINBOARD_PROXIMITY: CommandKey = smc_key(b"Tm0P");
-
-static TEMP_PALM_REST_1: CommandKey = smc_key(b"Ts0P");
-static TEMP_PALM_REST_2: CommandKey = smc_key(b"Ts1P");
-static TEMP_AIRPORT: CommandKey = smc_key(b"TW0P");
-static TEMP_AIRFLOW_LEFT: CommandKey = smc_key(b"TaLC");
-static TEMP_AIRFLOW_RIGHT: CommandKey = smc_key(b"TaRC");
-static TEMP_THUNDERBOLT_LEFT: CommandKey = smc_key(b"TTLD");
-static TEMP_THUNDERBOLT_RIGHT: CommandKey = smc_key(b"TTRD");
-
-static POWER_CPU_CORE: CommandKey = smc_key(b"PCPC");
-static POWER_CPU_DRAM: CommandKey = smc_key(b"PCPD");
-static POWER_CPU_GFX: CommandKey = smc_key(b"PCPG");
-static POWER_CPU_RAIL: CommandKey = smc_key(b"PC0R");
-static POWER_CPU_TOTAL: CommandKey = smc_key(b"PCPT");
-static POWER_DC_IN: CommandKey = smc_key(b"PDTR");
-static POWER_GPU_RAIL: CommandKey = smc_key(b"PG0R");
-static POWER_SYSTEM_TOTAL: CommandKey = smc_key(b"PSTR");
+    /// Returns the unit of the value.
+    pub fn unit(self) -> Unit {
+        match self {
+            Reading::Temperature(_) => Unit::Celsius,
+            Reading::Fan(_) => Unit::Rpm,
+            Reading::Power(_) => Unit::Watt,
+            Reading::Voltage(_) => Unit::Volt,
+            Reading::Current(_) => Unit::Ampere,
+        }
+    }
 
-const fn smc_key(key: &'static [u8]) -> CommandKey {
-    let key = [key[0], key[1], key[2], key[3]];
-    let key = u32::from_be_bytes(key);
-    CommandKey(key)
+    fn name(self) -> &'static str {
+        match self {
+            Reading::Temperature(_) => "Temperature",
+            Reading::Fan(_) => "Fan",
+            Reading::Power(_) => "Power",
+            Reading::Voltage(_) => "Voltage",
+            Reading::Current(_) => "Current",
+        }
+    }
+
+    fn read(backend: &mut Backend, key: &str, data_type: &str) -> InternalResult<Option<Self>> {
+        let command = Check(key).key();
+        // flags and other values that happen to share a prefix with the sensors are skipped
+        let reading = match (key.as_bytes(), data_type) {
+            ([b'T', ..], "sp78") | ([b'T', ..], "flt ") => {
+                backend.read_sensor(command)?.map(Reading::Temperature)
+            }
+            ([b'F', index, b'A', b'c'], _) if index.is_ascii_digit() => {
+                backend.read_sensor(command)?.map(Reading::Fan)
+            }
+            ([b'P', ..], _) => backend.read_sensor(command)?.map(Reading::Power),
+            ([b'V', ..], _) => backend.read_sensor(command)?.map(Reading::Voltage),
+            ([b'I', ..], _) => backend.read_sensor(command)?.map(Reading::Current),
+            _ => None,
+        };
+        Ok(reading)
+    }
 }
 
-read_impl!(NumberOfKeys = NUMBER_OF_KEYS -> u32);
-
-read_impl!(GetNumberOfFans = NUM_FANS -> u8);
-read_impl!(GetActualFanSpeed(u8) = FAN_SPEED_ACTUAL -> Rpm);
-read_impl!(GetMinFanSpeed(u8) = FAN_SPEED_MIN -> Rpm);
-read_impl!(GetMaxFanSpeed(u8) = FAN_SPEED_MAX -> Rpm);
-read_impl!(GetTargetFanSpeed(u8) = FAN_SPEED_TARGET -> Rpm);
-read_impl!(GetSafeFanSpeed(u8) = FAN_SPEED_SAFE -> Rpm);
-read_impl!(GetFanMode(u8) = FAN_MODE -> FanMode);
-
-read_impl!(GetNumberOfBatteries = NUM_BATTERIES -> u8);
-read_impl!(IsBatteryPowered = BATTERY_POWERED -> bool);
-read_impl!(GetBatteryInfo = BATTERY_INFO -> BatteryStatus);
-read_impl!(GetBatteryCycleCount(u8) = BATTERY_CYCLES -> u32);
-read_impl!(GetBatteryCurrentCapacity(u8) = BATTERY_CURRENT_CAPACITY -> MilliAmpereHours);
-read_impl!(GetBatteryFullCapacity(u8) = BATTERY_FULL_CAPACITY -> MilliAmpereHours);
-read_impl!(GetBatteryAmperage(u8) = BATTERY_AMPERAGE -> MilliAmpere);
-read_impl!(GetBatteryVoltage(u8) = BATTERY_VOLTAGE -> Volt);
-read_impl!(GetBatteryPower(u8) = BATTERY_POWER -> Watt);
-read_impl!(GetBatteryTemperatureMax = TEMP_BATTERY_MAX -> Celsius);
-read_impl!(GetBatteryTemperature1 = TEMP_BATTERY_1 -> Celsius);
-read_impl!(GetBatteryTemperature2 = TEMP_BATTERY_2 -> Celsius);
-
-read_impl!(CpuProximityTemperature = TEMP_CPU_PROXIMITY -> Celsius);
-read_impl!(CpuDieTemperature = TEMP_CPU_DIE -> Celsius);
-read_impl!(CpuGfxTemperature = TEMP_CPU_GFX -> Celsius);
-read_impl!(CpuSystemAgentTemperature = TEMP_CPU_SYSTEM_AGENT -> Celsius);
-read_impl!(CpuCoreTemperature(u8) == TEMP_CPU_CORE -> Celsius);
-
-read_impl!(GpuProximityTemperature = TEMP_GPU_PROXIMITY -> Celsius);
-read_impl!(GpuDieTemperature = TEMP_GPU_DIE -> Celsius);
-
-read_impl!(GetMemoryBankProximityTemperature = TEMP_MEM_PROXIMITY -> Celsius);
-read_impl!(GetMainboardProximityTemperature = TEMP_MAINBOARD_PROXIMITY -> Celsius);
-read_impl!(GetPCHDieTemperature = TEMP_PLATFORM_CONTROLLER_HUB_DIE -> Celsius);
-read_impl!(GetAirportTemperature = TEMP_AIRPORT -> Celsius);
-read_impl!(GetAirflowLeftTemperature = TEMP_AIRFLOW_LEFT -> Celsius);
-read_impl!(GetAirflowRightTemperature = TEMP_AIRFLOW_RIGHT -> Celsius);
-read_impl!(GetThunderboltLeftTemperature = TEMP_THUNDERBOLT_LEFT -> Celsius);
-read_impl!(GetThunderboltRightTemperature = TEMP_THUNDERBOLT_RIGHT -> Celsius);
-read_impl!(GetHeatpipe1Temperature = TEMP_HEATPIPE_1 -> Celsius);
-read_impl!(GetHeatpipe2Temperature = TEMP_HEATPIPE_2 -> Celsius);
-read_impl!(GetPalmRest1Temperature = TEMP_PALM_REST_1 -> Celsius);
-read_impl!(GetPalmRest2Temperature = TEMP_PALM_REST_2 -> Celsius);
-
-read_impl!(CpuCorePower = POWER_CPU_CORE -> Watt);
-read_impl!(CpuDramPower = POWER_CPU_DRAM -> Watt);
-read_impl!(CpuGfxPower = POWER_CPU_GFX -> Watt);
-read_impl!(CpuRailPower = POWER_CPU_RAIL -> Watt);
-read_impl!(CpuTotalPower = POWER_CPU_TOTAL -> Watt);
-read_impl!(GpuRailPower = POWER_GPU_RAIL -> Watt);
-read_impl!(DcInPower = POWER_DC_IN -> Watt);
-read_impl!(SystemTotalPower = POWER_SYSTEM_TOTAL -> Watt);
+impl Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reading::Temperature(value) => Display::fmt(value, f),
+            Reading::Fan(value) => Display::fmt(value, f),
+            Reading::Power(value) => Display::fmt(value, f),
+            Reading::Voltage(value) => Display::fmt(value, f),
+            Reading::Current(value) => Display::fmt(value, f),
+        }
+    }
+}
 
-macro_rules! int_tpe {
-    ($data:ident as $narrow:ty as $wide:ty as $out:ident) => {{
-        Ok($crate::DataValue::$out(<$wide>::from(
-            <$narrow>::from_be_bytes($data.try_into()?),
-        )))
-    }};
-    ($data:ident as $wide:ty as $out:ident) => {{
-        Ok($crate::DataValue::$out(<$wide>::from_be_bytes(
-            $data.try_into()?,
-        )))
-    }};
+/// A sensor that was found by [`Smc::discover`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiscoveredSensor {
+    /// The key of the sensor
+    pub key: String,
+    /// The label from the [`Catalog`], or a placeholder like `Temperature Ts1S`
+    /// if the sensor is not in the catalog
+    pub label: String,
+    /// True if the sensor is in the [`Catalog`]
+    pub known: bool,
+    /// The current value of the sensor
+    pub reading: Reading,
 }
 
-impl DataValue {
-    fn convert(data: &[u8], tpe: u32) -> InternalResult<Self> {
-        let tpe_str = tpe.to_be_bytes();
+/// The current value of a sensor in the [`Catalog`], as returned by [`Smc::sensors`].
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # fn main() -> Result<()> {
+/// let mut smc = Smc::connect()?;
+/// for sensor in smc.sensors()? {
+///     println!("{}: {} {}", sensor.label, sensor.value, sensor.unit.symbol());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sensor {
+    /// The key of the sensor
+    pub key: String,
+    /// The id of the sensor in the [`Catalog`]
+    pub id: String,
+    /// The label of the sensor, with the number of the fan, battery, etc. filled in
+    pub label: String,
+    /// The category of the sensor
+    pub kind: Category,
+    /// The unit of the value
+    pub unit: Unit,
+    /// The current value of the sensor, in the given unit.
+    /// Flags are reported as 0.0 or 1.0
+    pub value: f32,
+    /// Whether the value can be trusted, see [`Plausibility`]
+    pub quality: Quality,
+}
 
-        match &tpe_str {
-            b"flag" => return Ok(DataValue::Flag(!data.is_empty() && data[0] != 0)),
-            b"flt " => return Ok(DataValue::Float(f32::from_ne_bytes(data.try_into()?))),
-            b"hex_" => match data.len() {
-                1 => return int_tpe!(data as u8 as u64 as Uint),
-                2 => return int_tpe!(data as u16 as u64 as Uint),
-                4 => return int_tpe!(data as u32 as u64 as Uint),
-                8 => return int_tpe!(data as u64 as u64 as Uint),
-                _ => {}
-            },
-            b"ch8*" => {
-                let has_nul_termiantor = data.contains(&0);
-                let s = if has_nul_termiantor {
-                    unsafe { ::std::ffi::CStr::from_ptr(data.as_ptr() as *const _) }
-                        .to_string_lossy()
-                        .into_owned()
-                } else {
-                    let mut data = data.to_vec();
-                    data.push(0);
-                    unsafe { ::std::ffi::CStr::from_ptr(data.as_ptr() as *const _) }
-                        .to_string_lossy()
-                        .into_owned()
-                };
-                return Ok(DataValue::Str(s));
-            }
-            _ => {}
+/// Whether a reading can be trusted, as reported by [`Plausibility::check`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Quality {
+    /// The value is plausible
+    Good,
+    /// The value is outside of the [`Unit::plausible_range`], the sensor is broken or missing
+    Implausible,
+    /// The sensor reported the exact same value for a long time, it is probably stuck
+    Stuck,
+}
+
+impl Quality {
+    /// Returns true if the value can be trusted.
+    pub fn is_good(self) -> bool {
+        self == Quality::Good
+    }
+}
+
+/// Checks readings for plausibility across several samples.
+///
+/// A reading is implausible if it is outside of the physical range of its [`Unit`],
+/// and stuck if a sensor that normally fluctuates, e.g. a temperature or power sensor,
+/// reported the exact same non-zero value for longer than the configured duration.
+/// Fan speeds, capacities, percentages and flags are never considered stuck.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # use std::time::{Duration, Instant};
+/// let mut plausibility = Plausibility::new(Duration::from_secs(60));
+/// let start = Instant::now();
+/// let check = |plausibility: &mut Plausibility, value, secs| {
+///     plausibility.check("TC0P", Unit::Celsius, value, start + Duration::from_secs(secs))
+/// };
+/// assert_eq!(check(&mut plausibility, 42.0, 0), Quality::Good);
+/// assert_eq!(check(&mut plausibility, 42.0, 30), Quality::Good);
+/// assert_eq!(check(&mut plausibility, 42.0, 90), Quality::Stuck);
+/// assert_eq!(check(&mut plausibility, 42.5, 100), Quality::Good);
+/// assert_eq!(check(&mut plausibility, -127.0, 110), Quality::Implausible);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Plausibility {
+    stuck_after: Duration,
+    last: HashMap<String, (f32, Instant)>,
+}
+
+impl Plausibility {
+    /// The duration that [`Plausibility::default`] uses to detect stuck sensors.
+    pub const DEFAULT_STUCK_AFTER: Duration = Duration::from_secs(60 * 60);
+
+    /// Creates a check that considers a sensor stuck once it reported
+    /// the same value for longer than the given duration.
+    pub fn new(stuck_after: Duration) -> Self {
+        Self {
+            stuck_after,
+            last: HashMap::new(),
         }
+    }
 
-        match &tpe_str[..2] {
-            b"fp" => {
-                // fpXY, unsigned fixed point floats, X = integer width, Y = floating width
-                let i = char_to_int(tpe_str[2]);
-                let f = char_to_int(tpe_str[3]);
-                if i + f == 16 {
-                    let unsigned = u16::from_be_bytes(data.try_into()?);
-                    return decode_fp_float(f32::from(unsigned), f);
+    /// Checks a reading of the sensor with the given key, taken at the given instant.
+    /// Readings of the same sensor must be checked in chronological order.
+    pub fn check(&mut self, key: &str, unit: Unit, value: f32, at: Instant) -> Quality {
+        if !unit.is_plausible(value) {
+            let _ = self.last.remove(key);
+            return Quality::Implausible;
+        }
+        if !unit.fluctuates() || value == 0.0 {
+            return Quality::Good;
+        }
+        match self.last.get_mut(key) {
+            Some((last, since)) if *last == value => {
+                if at.saturating_duration_since(*since) > self.stuck_after {
+                    Quality::Stuck
+                } else {
+                    Quality::Good
                 }
             }
-            b"sp" => {
-                // spXY, signed fixed point floats, X = integer width, Y = floating width
-                let i = char_to_int(tpe_str[2]);
-                let f = char_to_int(tpe_str[3]);
-                if i + f == 15 {
-                    let signed = i16::from_be_bytes(data.try_into()?);
-                    return decode_fp_float(f32::from(signed), f);
-                }
+            Some(last) => {
+                *last = (value, at);
+                Quality::Good
+            }
+            None => {
+                let _ = self.last.insert(key.to_string(), (value, at));
+                Quality::Good
             }
-            b"ui" => match &tpe_str[2..] {
-                b"8 " => return int_tpe!(data as u8 as u64 as Uint),
-                b"16" => return int_tpe!(data as u16 as u64 as Uint),
-                b"32" => return int_tpe!(data as u32 as u64 as Uint),
-                b"64" => return int_tpe!(data as u64 as Uint),
-                _ => {}
-            },
-            b"si" => match &tpe_str[2..] {
-                b"8 " => return int_tpe!(data as i8 as i64 as Int),
-                b"16" => return int_tpe!(data as i16 as i64 as Int),
-                b"32" => return int_tpe!(data as i32 as i64 as Int),
-                b"64" => return int_tpe!(data as i64 as Int),
-                _ => {}
-            },
-            _ => {}
         }
+    }
 
-        Ok(DataValue::Unknown(data.to_vec()))
+    /// Checks all sensors, e.g. as returned by [`Smc::sensors`], and updates their quality.
+    pub fn check_sensors(&mut self, sensors: &mut [Sensor], at: Instant) {
+        for sensor in sensors {
+            sensor.quality = self.check(&sensor.key, sensor.unit, sensor.value, at);
+        }
     }
 }
 
-fn char_to_int(c: u8) -> u8 {
-    static A: u8 = b'a';
-    static F: u8 = b'f';
-    static N0: u8 = b'0';
-    static N9: u8 = b'9';
-
-    if c >= A && c <= F {
-        c - A + 10
-    } else if c >= N0 && c <= N9 {
-        c - N0
-    } else {
-        0
+impl Default for Plausibility {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_STUCK_AFTER)
     }
 }
 
-#[inline]
-fn decode_fp_float(float: f32, f: u8) -> InternalResult<DataValue> {
-    Ok(DataValue::Float(float / f32::from(1_u16 << f)))
-}
+/// Decides at which values a sensor is considered warm, hot, etc.
+/// The 4 thresholds partition the values of a sensor into one of 5 buckets,
+/// e.g. a temperature above `thresholds[3]` is critical.
+///
+/// Sensors are identified by the id of their [`CatalogEntry`], e.g. `cpu.die` or `palm_rest.1`.
+/// Implement this trait to use thresholds that fit a specific machine better,
+/// and set it with [`Smc::set_threshold_policy`].
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// #[derive(Debug)]
+/// struct Quiet;
+///
+/// impl ThresholdPolicy for Quiet {
+///     fn temperature(&self, id: &str, platform: Platform, model: &str) -> [Celsius; 4] {
+///         [Celsius(40.0), Celsius(50.0), Celsius(60.0), Celsius(70.0)]
+///     }
+///
+///     fn power(&self, id: &str, platform: Platform, model: &str) -> [Watt; 4] {
+///         DefaultThresholds.power(id, platform, model)
+///     }
+/// }
+///
+/// let thresholds = Quiet.temperature("cpu.die", Platform::AppleSilicon(1), "");
+/// assert_eq!(thresholds[3], Celsius(70.0));
+/// ```
+pub trait ThresholdPolicy: fmt::Debug + Send + Sync {
+    /// Returns the thresholds for the temperature sensor with the given id.
+    fn temperature(&self, id: &str, platform: Platform, model: &str) -> [Celsius; 4];
 
-impl Into<u32> for DataType {
-    fn into(self) -> u32 {
-        self.1
-    }
+    /// Returns the thresholds for the power sensor with the given id.
+    fn power(&self, id: &str, platform: Platform, model: &str) -> [Watt; 4];
 }
 
-#[derive(Debug)]
-struct KeyInfo {
-    key: u32,
-    data_type: u32,
-    data_size: u32,
+/// The thresholds that are used unless a different [`ThresholdPolicy`] is set.
+///
+/// Temperatures of the CPU and GPU dies are derived from the maximum junction temperature
+/// of the platform (see [`DefaultThresholds::tj_max`]), palm rests and batteries use
+/// lower limits that are uncomfortable to touch or wear the battery,
+/// and every other sensor uses [`Celsius::thresholds`].
+/// Power thresholds are lower on Apple Silicon, where CPU and GPU draw a lot less power,
+/// and [`Watt::thresholds`] otherwise.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// let intel = Platform::Intel;
+/// assert_eq!(DefaultThresholds::tj_max(intel, ""), Celsius(100.0));
+/// assert_eq!(DefaultThresholds.temperature("cpu.die", intel, "")[3], Celsius(95.0));
+/// assert_eq!(DefaultThresholds.temperature("palm_rest.1", intel, "")[3], Celsius(48.0));
+/// let mainboard = DefaultThresholds.temperature("mainboard.proximity", intel, "");
+/// assert_eq!(mainboard, Celsius::thresholds());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DefaultThresholds;
+
+/// The maximum junction temperature, matched against the platform and model
+/// the same way as the models column of the catalog. The first match wins.
+const TJ_MAX: [(&str, f32); 2] = [("intel", 100.0), ("apple", 105.0)];
+
+/// Sensors that measure the temperature of a CPU or GPU die.
+const DIE_SENSORS: [&str; 6] = [
+    "cpu.die",
+    "cpu.core",
+    "cpu.graphics",
+    "cpu.system_agent",
+    "gpu.die",
+    "gpu.cluster",
+];
+
+impl DefaultThresholds {
+    /// Returns the maximum junction temperature of the CPU, at which it starts to throttle.
+    pub fn tj_max(platform: Platform, model: &str) -> Celsius {
+        TJ_MAX
+            .iter()
+            .find(|(token, _)| model_matches(token, platform, model))
+            .map_or(Celsius(100.0), |&(_, tj_max)| Celsius(tj_max))
+    }
 }
 
-impl StdError for Error {}
+impl ThresholdPolicy for DefaultThresholds {
+    fn temperature(&self, id: &str, platform: Platform, model: &str) -> [Celsius; 4] {
+        if DIE_SENSORS.iter().any(|sensor| id_matches(sensor, id)) {
+            let tj_max = *Self::tj_max(platform, model);
+            [
+                Celsius(tj_max - 45.0),
+                Celsius(tj_max - 30.0),
+                Celsius(tj_max - 15.0),
+                Celsius(tj_max - 5.0),
+            ]
+        } else if id_matches("palm_rest", id) {
+            [Celsius(35.0), Celsius(40.0), Celsius(43.0), Celsius(48.0)]
+        } else if id_matches("battery", id) {
+            [Celsius(35.0), Celsius(40.0), Celsius(45.0), Celsius(50.0)]
+        } else {
+            Celsius::thresholds()
+        }
+    }
 
-impl Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Error::SmcNotAvailable => write!(f, "SMC is not available, are you running on a Mac?"),
-            Error::InsufficientPrivileges => {
-                write!(f, "Could not perform SMC operation, try running with sudo")
+    fn power(&self, id: &str, platform: Platform, _model: &str) -> [Watt; 4] {
+        match platform {
+            Platform::AppleSilicon(_) if id_matches("power.cpu", id) => {
+                [Watt(5.0), Watt(10.0), Watt(20.0), Watt(30.0)]
             }
-            Error::SmcError(code) => write!(f, "Could not perform SMC operation: {:08x}", code),
-            Error::DataError { key, tpe } => write!(
-                f,
-                "Could not read data for key {} of type {}",
-                tpe_name(key),
-                tpe_name(tpe)
-            ),
+            Platform::AppleSilicon(_) if id_matches("power.gpu", id) => {
+                [Watt(5.0), Watt(10.0), Watt(20.0), Watt(40.0)]
+            }
+            _ => Watt::thresholds(),
         }
     }
 }
 
-fn tpe_name(tpe: &u32) -> String {
-    let bytes = tpe.to_be_bytes();
-    String::from_utf8_lossy(&bytes).to_string()
+/// A [`ThresholdPolicy`] that overrides the thresholds of some sensors
+/// and uses another policy for all other sensors.
+///
+/// An override applies to the sensor with the given id and to all sensors below it,
+/// e.g. `cpu` applies to `cpu.die` and `cpu.core.performance`, but not to `cpu_fan`.
+/// Later overrides take precedence over earlier ones.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// let policy = CustomThresholds::new()
+///     .override_temperature("cpu", [Celsius(60.0), Celsius(70.0), Celsius(80.0), Celsius(90.0)])
+///     .override_temperature("cpu.die", [Celsius(70.0), Celsius(80.0), Celsius(90.0), Celsius(100.0)]);
+/// let intel = Platform::Intel;
+/// assert_eq!(policy.temperature("cpu.core", intel, "")[0], Celsius(60.0));
+/// assert_eq!(policy.temperature("cpu.die", intel, "")[0], Celsius(70.0));
+/// assert_eq!(policy.temperature("palm_rest.1", intel, "")[0], Celsius(35.0));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CustomThresholds<P = DefaultThresholds> {
+    fallback: P,
+    temperatures: Vec<(String, [Celsius; 4])>,
+    power: Vec<(String, [Watt; 4])>,
 }
 
-type InternalResult<T> = std::result::Result<T, InternalError>;
-enum InternalError {
-    SmcNotFound,
-    SmcFailedToOpen(i32),
-    NotPrivlileged,
-    UnknownSmc(i32, u8),
-    _UnknownKey,
-    _DataKeyError(u32),
-    _DataValueError,
-    // for pub error
-    DataError { key: u32, tpe: u32 },
+impl CustomThresholds {
+    /// Creates a policy without any overrides, that uses the [`DefaultThresholds`].
+    pub fn new() -> Self {
+        Self::default()
+    }
 }
 
-impl From<TryFromSliceError> for InternalError {
-    fn from(_: TryFromSliceError) -> Self {
-        Self::_DataValueError
+impl<P: ThresholdPolicy> CustomThresholds<P> {
+    /// Creates a policy without any overrides, that uses the given policy.
+    pub fn with_fallback(fallback: P) -> Self {
+        Self {
+            fallback,
+            temperatures: Vec::new(),
+            power: Vec::new(),
+        }
     }
-}
 
-impl From<TryFromIntError> for InternalError {
-    fn from(_: TryFromIntError) -> Self {
-        Self::_DataValueError
+    /// Overrides the thresholds of the temperature sensors with the given id.
+    pub fn override_temperature(mut self, id: impl Into<String>, thresholds: [Celsius; 4]) -> Self {
+        self.temperatures.push((id.into(), thresholds));
+        self
+    }
+
+    /// Overrides the thresholds of the power sensors with the given id.
+    pub fn override_power(mut self, id: impl Into<String>, thresholds: [Watt; 4]) -> Self {
+        self.power.push((id.into(), thresholds));
+        self
     }
 }
 
-impl From<InternalError> for Error {
-    fn from(ie: InternalError) -> Self {
-        match ie {
-            InternalError::SmcNotFound => Error::SmcNotAvailable,
-            InternalError::SmcFailedToOpen(_) => Error::SmcNotAvailable,
-            InternalError::NotPrivlileged => Error::InsufficientPrivileges,
-            InternalError::UnknownSmc(code, _) => Error::SmcError(code),
-            InternalError::DataError { key, tpe } => Error::DataError { key, tpe },
-            InternalError::_UnknownKey => unreachable!(),
-            InternalError::_DataValueError => unreachable!(),
-            InternalError::_DataKeyError(_) => unreachable!(),
+impl<P: ThresholdPolicy> ThresholdPolicy for CustomThresholds<P> {
+    fn temperature(&self, id: &str, platform: Platform, model: &str) -> [Celsius; 4] {
+        match find_override(&self.temperatures, id) {
+            Some(thresholds) => thresholds,
+            None => self.fallback.temperature(id, platform, model),
+        }
+    }
+
+    fn power(&self, id: &str, platform: Platform, model: &str) -> [Watt; 4] {
+        match find_override(&self.power, id) {
+            Some(thresholds) => thresholds,
+            None => self.fallback.power(id, platform, model),
         }
     }
 }
 
-mod cffi {
-    use super::*;
-    #[cfg(target_os = "macos")]
-    use std::{ffi::CStr, ptr};
-    use std::{mem::size_of, os::raw::c_void};
+fn find_override<T: Copy>(overrides: &[(String, [T; 4])], id: &str) -> Option<[T; 4]> {
+    overrides
+        .iter()
+        .rev()
+        .find(|(prefix, _)| id_matches(prefix, id))
+        .map(|&(_, thresholds)| thresholds)
+}
 
-    #[allow(non_camel_case_types)]
-    type kern_return_t = i32;
-    #[allow(non_camel_case_types)]
-    type ipc_port_t = *mut c_void;
-    #[allow(non_camel_case_types)]
-    type mach_port_t = ipc_port_t;
-    #[allow(non_camel_case_types)]
-    type io_object_t = mach_port_t;
-    #[allow(non_camel_case_types)]
-    type io_connect_t = io_object_t;
-    #[allow(non_camel_case_types)]
-    type task_t = *mut c_void;
-    #[allow(non_camel_case_types)]
-    type task_port_t = task_t;
-    #[allow(non_camel_case_types)]
-    type io_service_t = io_object_t;
+/// Returns true if the id is the given one or below it, e.g. `cpu` matches `cpu.die`.
+fn id_matches(prefix: &str, id: &str) -> bool {
+    match id.strip_prefix(prefix) {
+        Some(rest) => rest.is_empty() || rest.starts_with('.'),
+        None => false,
+    }
+}
 
-    const MACH_PORT_NULL: mach_port_t = 0 as mach_port_t;
-    const MASTER_PORT_DEFAULT: mach_port_t = MACH_PORT_NULL;
+/// Return type for a debug command. Does not interpret the data.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dbg {
+    /// The key for the data
+    pub key: String,
+    /// An error if the data could not be fetched
+    /// None if the key does not exist
+    /// Some(value) for other cases
+    pub value: Result<Option<DataValue>>,
+}
 
-    const KERN_SUCCESS: kern_return_t = 0;
-    const RETURN_SUCCESS: kern_return_t = KERN_SUCCESS;
+/// Return type for a debug command. Does not interpret the data.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DbgKeyInfo {
+    /// The key for the data
+    pub key: String,
+    /// The expected type of the data
+    pub data_type: String,
+    /// The expected number of bytes to read for the data
+    pub data_size: usize,
+}
 
-    const SYS_IOKIT: kern_return_t = (0x38 & 0x3f) << 26;
-    const SUB_IOKIT_COMMON: kern_return_t = 0;
-    const RETURN_NOT_PRIVILEGED: kern_return_t = SYS_IOKIT | SUB_IOKIT_COMMON | 0x2c1;
+/// The SMC client.
+/// All methods take self as a mutable reference, even though
+/// it is _technically_ not required.
+/// This is to make sure, that a single connection can only be used
+/// by one reference at a time.
+/// Use [`SharedSmc`] to share a single connection between threads.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # fn main() -> Result<()> {
+/// let mut smc = Smc::connect()?;
+/// let cpu_temp = smc.cpu_temperature()?;
+/// assert!(*cpu_temp.proximity > 0.0);
+/// // will disconnect
+/// drop(smc);
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(doc, doc(cfg(target_os = "macos")))]
+#[derive(Debug)]
+pub struct Smc {
+    inner: Backend,
+    platform: Platform,
+    model: String,
+    catalog: Catalog,
+    thresholds: Box<dyn ThresholdPolicy>,
+    plausibility: Plausibility,
+    perf_levels: Option<[u8; 2]>,
+    cpu_cores: Option<Vec<CpuCore>>,
+    system_info: Option<SystemInfo>,
+}
 
-    const KERNEL_INDEX_SMC: u32 = 2;
+impl Smc {
+    #![cfg_attr(doc, doc(cfg(target_os = "macos")))]
+
+    /// Creates a new connection to the SMC system.
+    ///
+    /// # Errors
+    /// [`Error::SmcNotAvailable`] If the SMC system is not available
+    pub fn connect() -> Result<Self> {
+        let inner = Backend::Smc(cffi::SMCConnection::new()?);
+        let platform = cffi::platform();
+        let model = cffi::model();
+        let perf_levels = cffi::perf_levels();
+        Ok(Smc {
+            inner,
+            platform,
+            model,
+            catalog: Catalog::builtin(),
+            thresholds: Box::new(DefaultThresholds),
+            plausibility: Plausibility::default(),
+            perf_levels,
+            cpu_cores: None,
+            system_info: None,
+        })
+    }
+
+    /// Returns the [`Platform`] that this SMC is running on.
+    pub fn platform(&self) -> Platform {
+        self.platform
+    }
+
+    /// Returns the model identifier of this machine, e.g. `MacBookPro15,1`.
+    /// The model is empty if it could not be determined.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Returns the [`Catalog`] that is used to look up the keys for the typed getters.
+    pub fn catalog(&self) -> &Catalog {
+        &self.catalog
+    }
+
+    /// Extends the [`Catalog`] that is used to look up the keys for the typed getters,
+    /// e.g. with a catalog file that adds the keys of a model that is not supported yet.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # fn main() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// let mut smc = Smc::connect()?;
+    /// smc.extend_catalog(Catalog::parse(
+    ///     "TC1P | cpu.proximity | cpu | °C | CPU Proximity | MacBookPro99 | A future model",
+    /// )?);
+    /// let cpu_temp = smc.cpu_temperature()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn extend_catalog(&mut self, catalog: Catalog) {
+        self.catalog.extend(catalog);
+        self.cpu_cores = None;
+    }
+
+    /// Sets the [`ThresholdPolicy`] that decides at which values a sensor is considered hot.
+    /// The [`DefaultThresholds`] are used if no policy is set.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # fn main() -> Result<()> {
+    /// let mut smc = Smc::connect()?;
+    /// let palm_rest = [Celsius(30.0), Celsius(35.0), Celsius(40.0), Celsius(45.0)];
+    /// smc.set_threshold_policy(CustomThresholds::new().override_temperature("palm_rest", palm_rest));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_threshold_policy(&mut self, policy: impl ThresholdPolicy + 'static) {
+        self.thresholds = Box::new(policy);
+    }
+
+    /// Returns the [`ThresholdPolicy`] that decides at which values a sensor is considered hot.
+    pub fn threshold_policy(&self) -> &dyn ThresholdPolicy {
+        &*self.thresholds
+    }
+
+    /// Returns the thresholds for the temperature sensor with the given catalog id,
+    /// according to the [`ThresholdPolicy`] of this machine.
+    pub fn temperature_thresholds(&self, id: &str) -> [Celsius; 4] {
+        self.thresholds.temperature(id, self.platform, &self.model)
+    }
+
+    /// Returns the thresholds for the power sensor with the given catalog id,
+    /// according to the [`ThresholdPolicy`] of this machine.
+    pub fn power_thresholds(&self, id: &str) -> [Watt; 4] {
+        self.thresholds.power(id, self.platform, &self.model)
+    }
+
+    fn profile(&self) -> &'static SensorProfile {
+        match self.platform {
+            Platform::Intel => &INTEL_PROFILE,
+            Platform::AppleSilicon(_) => &APPLE_SILICON_PROFILE,
+        }
+    }
+
+    /// Returns the keys of all catalog entries with the given id that apply to this machine,
+    /// with the placeholder replaced by the given index.
+    fn keys(&self, id: &str, index: u8) -> Vec<CommandKey> {
+        self.catalog
+            .entries_by_id(id)
+            .filter(|entry| entry.applies_to(self.platform, &self.model))
+            .filter_map(|entry| entry.command_key(index))
+            .collect()
+    }
+
+    fn read<T: ValueParser + Default>(&mut self, id: &str) -> Result<T> {
+        self.read_indexed(id, 0)
+    }
+
+    fn read_indexed<T: ValueParser + Default>(&mut self, id: &str, index: u8) -> Result<T> {
+        Ok(self.opt_read_indexed(id, index)?.unwrap_or_default())
+    }
+
+    fn opt_read_indexed<T: ValueParser>(&mut self, id: &str, index: u8) -> Result<Option<T>> {
+        for key in self.keys(id, index) {
+            if let Some(value) = self.inner.opt_read_value(ReadKey::new(key))? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(None)
+    }
+
+    fn write<T: ValueParser + ValueEncoder>(&mut self, id: &str, value: T) -> Result<()> {
+        let not_available = || Error::KeyNotAvailable(id.to_string());
+        let key = self
+            .keys(id, 0)
+            .into_iter()
+            .next()
+            .ok_or_else(not_available)?;
+        self.inner
+            .write_value(ReadKey::new(key), value)
+            .map_err(|e| match e {
+                InternalError::KeyNotAvailable(_) => not_available(),
+                otherwise => Error::from(otherwise),
+            })
+    }
+
+    fn read_temperature(&mut self, sensors: &Sensors) -> Result<Celsius> {
+        Ok(Celsius(self.read_sensors::<Celsius>(sensors)?))
+    }
+
+    fn read_power(&mut self, sensors: &Sensors) -> Result<Watt> {
+        Ok(Watt(self.read_sensors::<Watt>(sensors)?))
+    }
+
+    fn read_sensors<T>(&mut self, sensors: &Sensors) -> Result<f32>
+    where
+        T: ValueParser + Deref<Target = f32>,
+    {
+        let mut values = Vec::new();
+        'ids: for &id in sensors.ids {
+            for key in self.keys(id, 0) {
+                if let Some(value) = self.inner.opt_read_value(ReadKey::<T>::new(key))? {
+                    values.push(*value);
+                    if let Reduce::First = sensors.reduce {
+                        break 'ids;
+                    }
+                }
+            }
+        }
+        Ok(sensors.reduce.apply(&values))
+    }
+
+    /// Returns an iterator over all [FanSpeed](struct.FanSpeed.html) items available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn fans(&mut self) -> Result<FanIter> {
+        FanIter::new(self)
+    }
+
+    fn number_of_fans(&mut self) -> Result<u8> {
+        self.read("fan.count")
+    }
+
+    fn fan_speed(&mut self, fan: u8) -> Result<FanSpeed> {
+        let actual = self.read_indexed("fan.actual", fan)?;
+        let min = self.read_indexed("fan.min", fan)?;
+        let max = self.read_indexed("fan.max", fan)?;
+        let target = self.read_indexed("fan.target", fan)?;
+        let safe = self.read_indexed("fan.safe", fan)?;
+        let mode = self.read_indexed("fan.mode", fan)?;
+        Ok(FanSpeed {
+            actual,
+            min,
+            max,
+            target,
+            safe,
+            mode,
+        })
+    }
+
+    /// Returns the overall [`BatteryInfo`]
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn battery_info(&mut self) -> Result<BatteryInfo> {
+        let BatteryStatus {
+            charging,
+            ac_present,
+            health_ok,
+        } = self.read("battery.info")?;
+        let battery_powered = self.read("battery.powered")?;
+        let temperature_max = self.read("battery.temperature.max")?;
+        let temperature_1 = self.read("battery.temperature.1")?;
+        let temperature_2 = self.read("battery.temperature.2")?;
+        Ok(BatteryInfo {
+            battery_powered,
+            charging,
+            ac_present,
+            health_ok,
+            temperature_max,
+            temperature_1,
+            temperature_2,
+        })
+    }
+
+    fn number_of_batteries(&mut self) -> Result<u8> {
+        self.read("battery.count")
+    }
+
+    /// Returns an iterator over all [`BatteryDetail`] items available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn battery_details(&mut self) -> Result<BatteryIter> {
+        Ok(BatteryIter::new(self)?)
+    }
+
+    fn battery_detail(&mut self, battery: u8) -> Result<BatteryDetail> {
+        let cycles = self.read_indexed("battery.cycles", battery)?;
+        let current_capacity = self.read_indexed("battery.capacity.current", battery)?;
+        let full_capacity = self.read_indexed("battery.capacity.full", battery)?;
+        let amperage = self.read_indexed("battery.amperage", battery)?;
+        let voltage = self.read_indexed("battery.voltage", battery)?;
+        let power = self.read_indexed("battery.power", battery)?;
+        Ok(BatteryDetail {
+            cycles,
+            current_capacity,
+            full_capacity,
+            amperage,
+            voltage,
+            power,
+        })
+    }
 
     #[cfg(target_os = "macos")]
-    pub(super) fn num_cpus() -> i32 {
-        let mut cpus: i32 = 0;
-        let mut cpus_size = std::mem::size_of_val(&cpus);
+    fn number_of_cpus(&mut self) -> Result<u8> {
+        Ok(self.cpu_cores()?.len().min(255) as u8)
+    }
 
-        let sysctl_name =
-            CStr::from_bytes_with_nul(b"hw.physicalcpu\0").expect("byte literal is missing NUL");
+    #[cfg(target_os = "macos")]
+    fn cpu_cores(&mut self) -> Result<&[CpuCore]> {
+        if self.cpu_cores.is_none() {
+            let cores = match self.profile().cpu_cores {
+                CoreSensors::Indexed(id) => {
+                    let cores = match self.perf_levels {
+                        Some([performance, efficiency]) => performance.saturating_add(efficiency),
+                        None => cffi::num_cpus().min(255) as u8,
+                    };
+                    (0..cores)
+                        .filter_map(|core| {
+                            let key = self.keys(id, core).into_iter().next()?;
+                            Some(CpuCore {
+                                kind: CoreKind::Performance,
+                                index: core,
+                                key,
+                            })
+                        })
+                        .collect()
+                }
+                CoreSensors::Listed {
+                    performance,
+                    efficiency,
+                } => {
+                    let [max_performance, max_efficiency] =
+                        self.perf_levels.unwrap_or([u8::MAX, u8::MAX]);
+                    let mut cores = Vec::new();
+                    for &(kind, id, max) in &[
+                        (CoreKind::Performance, performance, max_performance),
+                        (CoreKind::Efficiency, efficiency, max_efficiency),
+                    ] {
+                        let mut index = 0;
+                        for key in self.keys(id, 0) {
+                            if index >= max {
+                                break;
+                            }
+                            if self
+                                .inner
+                                .opt_read_value(ReadKey::<Celsius>::new(key))?
+                                .is_some()
+                            {
+                                cores.push(CpuCore { kind, index, key });
+                                index += 1;
+                            }
+                        }
+                    }
+                    cores
+                }
+            };
+            self.cpu_cores = Some(cores);
+        }
+        Ok(self.cpu_cores.as_deref().unwrap_or_default())
+    }
 
-        unsafe {
-            if 0 != libc::sysctlbyname(
-                sysctl_name.as_ptr(),
-                &mut cpus as *mut _ as *mut _,
-                &mut cpus_size as *mut _ as *mut _,
-                ptr::null_mut(),
-                0,
-            ) {
-                // On ARM targets, processors could be turned off to save power.
-                // Use `_SC_NPROCESSORS_CONF` to get the real number.
-                #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
-                const CONF_NAME: libc::c_int = libc::_SC_NPROCESSORS_CONF;
-                #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
-                const CONF_NAME: libc::c_int = libc::_SC_NPROCESSORS_ONLN;
+    /// Returns the overall [`CpuTemperatures`] available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn cpu_temperature(&mut self) -> Result<CpuTemperatures> {
+        let profile = self.profile();
+        let proximity = self.read_temperature(&profile.cpu_proximity)?;
+        let die = self.read_temperature(&profile.cpu_die)?;
+        let graphics = self.read_temperature(&profile.cpu_graphics)?;
+        let system_agent = self.read_temperature(&profile.cpu_system_agent)?;
+        Ok(CpuTemperatures {
+            proximity,
+            die,
+            graphics,
+            system_agent,
+        })
+    }
 
-                cpus = libc::sysconf(CONF_NAME) as i32;
+    /// Returns an iterator over all cpu core temperatures as [`CoreTemperature`]s.
+    /// Performance cores are listed before efficiency cores.
+    ///
+    /// On Apple Silicon, some chips report fewer sensors than there are cores in a cluster,
+    /// in which case only the cores with a sensor are listed.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    #[cfg(any(doc, target_os = "macos"))]
+    pub fn cpu_core_temps(&mut self) -> Result<CpuIter> {
+        Ok(CpuIter::new(self)?)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn cpu_core_temperature(&mut self, core: u8) -> Result<CoreTemperature> {
+        let CpuCore { kind, index, key } = *self
+            .cpu_cores()?
+            .get(usize::from(core))
+            .ok_or_else(|| Error::KeyNotAvailable(format!("CPU core {}", core)))?;
+        let temperature = self.inner.read_value(ReadKey::new(key))?;
+        Ok(CoreTemperature {
+            kind,
+            index,
+            temperature,
+        })
+    }
+
+    /// Returns the overall [`GpuTemperatures`] available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn gpu_temperature(&mut self) -> Result<GpuTemperatures> {
+        let profile = self.profile();
+        let proximity = self.read_temperature(&profile.gpu_proximity)?;
+        let die = self.read_temperature(&profile.gpu_die)?;
+        Ok(GpuTemperatures { proximity, die })
+    }
+
+    /// Returns an iterator over all [`GpuDetail`] items available.
+    /// Integrated and dedicated GPUs are listed as separate items.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn gpus(&mut self) -> Result<GpuIter<'_>> {
+        GpuIter::new(self)
+    }
+
+    fn number_of_gpus(&mut self) -> Result<u8> {
+        if let Platform::AppleSilicon(_) = self.platform {
+            // The GPU is integrated into the SoC, so there is at most one.
+            let profile = self.profile();
+            let gpu = self.read_sensors::<Celsius>(&profile.gpu_die)? != 0.0
+                || self.read_sensors::<Watt>(&profile.gpu_power)? != 0.0;
+            return Ok(u8::from(gpu));
+        }
+        // There is no key for the number of GPUs, so we look for the last index
+        // that has any sensor available.
+        let mut gpus = 0;
+        for gpu in 0..MAX_GPUS {
+            if self
+                .opt_read_indexed::<Celsius>("gpu.proximity", gpu)?
+                .is_some()
+                || self.opt_read_indexed::<Celsius>("gpu.die", gpu)?.is_some()
+                || self.opt_read_indexed::<Watt>("power.gpu", gpu)?.is_some()
+            {
+                gpus = gpu + 1;
+            }
+        }
+        Ok(gpus)
+    }
+
+    fn gpu_detail(&mut self, gpu: u8) -> Result<GpuDetail> {
+        if let Platform::AppleSilicon(_) = self.platform {
+            let profile = self.profile();
+            let proximity = self.read_temperature(&profile.gpu_proximity)?;
+            let die = self.read_temperature(&profile.gpu_die)?;
+            let power = self.read_power(&profile.gpu_power)?;
+            return Ok(GpuDetail {
+                proximity,
+                die,
+                power,
+            });
+        }
+        let proximity = self.read_indexed("gpu.proximity", gpu)?;
+        let die = self.read_indexed("gpu.die", gpu)?;
+        let power = self.read_indexed("power.gpu", gpu)?;
+        Ok(GpuDetail {
+            proximity,
+            die,
+            power,
+        })
+    }
+
+    /// Returns the overall information about [`OtherTemperatures`] available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn other_temperatures(&mut self) -> Result<OtherTemperatures> {
+        let memory_bank_proximity = self.read("memory.proximity")?;
+        let mainboard_proximity = self.read("mainboard.proximity")?;
+        let platform_controller_hub_die = self.read("pch.die")?;
+        let airport = self.read("airport.proximity")?;
+        let airflow_left = self.read("airflow.left")?;
+        let airflow_right = self.read("airflow.right")?;
+        let thunderbolt_left = self.read("thunderbolt.left")?;
+        let thunderbolt_right = self.read("thunderbolt.right")?;
+        let heatpipe_1 = self.read("heatpipe.1")?;
+        let heatpipe_2 = self.read("heatpipe.2")?;
+        let palm_rest_1 = self.read("palm_rest.1")?;
+        let palm_rest_2 = self.read("palm_rest.2")?;
+        Ok(OtherTemperatures {
+            memory_bank_proximity,
+            mainboard_proximity,
+            platform_controller_hub_die,
+            airport,
+            airflow_left,
+            airflow_right,
+            thunderbolt_left,
+            thunderbolt_right,
+            heatpipe_1,
+            heatpipe_2,
+            palm_rest_1,
+            palm_rest_2,
+        })
+    }
+
+    /// Returns an iterator over the [`DimmTemperatures`] of all memory modules available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn dimm_temperatures(&mut self) -> Result<DimmIter<'_>> {
+        DimmIter::new(self)
+    }
+
+    fn number_of_dimms(&mut self) -> Result<u8> {
+        // There is no key for the number of DIMMs, so we look for the last index
+        // that has any sensor available.
+        let mut dimms = 0;
+        for dimm in 0..MAX_DIMMS {
+            if self
+                .opt_read_indexed::<Celsius>("memory.proximity", dimm)?
+                .is_some()
+                || self
+                    .opt_read_indexed::<Celsius>("memory.slot", dimm)?
+                    .is_some()
+            {
+                dimms = dimm + 1;
             }
         }
+        Ok(dimms)
+    }
+
+    fn dimm_temperature(&mut self, dimm: u8) -> Result<DimmTemperatures> {
+        let proximity = self.read_indexed("memory.proximity", dimm)?;
+        let slot = self.read_indexed("memory.slot", dimm)?;
+        Ok(DimmTemperatures { proximity, slot })
+    }
+
+    /// Returns an iterator over the [`DriveTemperatures`] of all storage devices available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn drive_temperatures(&mut self) -> Result<DriveIter<'_>> {
+        DriveIter::new(self)
+    }
+
+    fn number_of_drives(&mut self) -> Result<u8> {
+        // There is no key for the number of drives, so we look for the last index
+        // that has any sensor available.
+        let mut drives = 0;
+        for drive in 0..MAX_DRIVES {
+            if self
+                .opt_read_indexed::<Celsius>("drive.proximity", drive)?
+                .is_some()
+                || self
+                    .opt_read_indexed::<Celsius>("drive.nand_1", drive)?
+                    .is_some()
+                || self
+                    .opt_read_indexed::<Celsius>("drive.nand_2", drive)?
+                    .is_some()
+            {
+                drives = drive + 1;
+            }
+        }
+        Ok(drives)
+    }
+
+    fn drive_temperature(&mut self, drive: u8) -> Result<DriveTemperatures> {
+        let proximity = self.read_indexed("drive.proximity", drive)?;
+        let nand_1 = self.read_indexed("drive.nand_1", drive)?;
+        let nand_2 = self.read_indexed("drive.nand_2", drive)?;
+        Ok(DriveTemperatures {
+            proximity,
+            nand_1,
+            nand_2,
+        })
+    }
+
+    /// Returns the overall [`CpuPower`] information available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn cpu_power(&mut self) -> Result<CpuPower> {
+        let profile = self.profile();
+        let core = self.read_power(&profile.cpu_core_power)?;
+        let dram = self.read_power(&profile.cpu_dram_power)?;
+        let gfx = self.read_power(&profile.cpu_gfx_power)?;
+        let rail = self.read_power(&profile.cpu_rail_power)?;
+        let total = self.read_power(&profile.cpu_total_power)?;
+        Ok(CpuPower {
+            core,
+            dram,
+            gfx,
+            rail,
+            total,
+        })
+    }
+
+    /// Returns the overall `GPUPower` information in [`Watt`] available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn gpu_power(&mut self) -> Result<Watt> {
+        let profile = self.profile();
+        self.read_power(&profile.gpu_power)
+    }
+
+    /// Returns the combined power consumption in [`Watt`] of the CPU package and the GPU,
+    /// see [`CpuPower::package`] and [`Smc::gpu_power`].
+    /// If the GPU is the graphics unit of the package, as on Apple Silicon,
+    /// it is only counted once.
+    /// Returns None if neither the package nor the GPU power is available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn package_with_gpu_power(&mut self) -> Result<Option<Watt>> {
+        let package = self.cpu_power()?.package().map_or(0.0, |package| *package);
+        let gpu = if self.gpu_is_in_package() {
+            0.0
+        } else {
+            *self.gpu_power()?
+        };
+        let sum = package + gpu;
+        Ok(if sum > 0.0 { Some(Watt(sum)) } else { None })
+    }
+
+    /// Returns true if the GPU power is read from a key of the package's graphics unit.
+    fn gpu_is_in_package(&self) -> bool {
+        let profile = self.profile();
+        let keys = |sensors: &Sensors| {
+            sensors
+                .ids
+                .iter()
+                .flat_map(|id| self.keys(id, 0))
+                .collect::<Vec<_>>()
+        };
+        let gfx = keys(&profile.cpu_gfx_power);
+        keys(&profile.gpu_power).iter().any(|key| gfx.contains(key))
+    }
+
+    /// Returns the current amount of power being in [`Watt`] drawn from DC.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn power_dc_in(&mut self) -> Result<Watt> {
+        self.read("power.dc_in")
+    }
+
+    /// Returns the overall power draw in [`Watt`] of the whole system.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn power_system_total(&mut self) -> Result<Watt> {
+        self.read("power.system_total")
+    }
+
+    /// Returns the overall [`Voltages`] information available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn voltages(&mut self) -> Result<Voltages> {
+        let cpu_core = self.read("voltage.cpu_core")?;
+        let cpu_vccio = self.read("voltage.cpu_vccio")?;
+        let cpu_system_agent = self.read("voltage.cpu_system_agent")?;
+        let gpu_core = self.read("voltage.gpu_core")?;
+        let memory = self.read("voltage.memory")?;
+        let platform_controller_hub = self.read("voltage.pch")?;
+        let dc_in = self.read("voltage.dc_in")?;
+        let rail_12v = self.read("voltage.rail_12v")?;
+        Ok(Voltages {
+            cpu_core,
+            cpu_vccio,
+            cpu_system_agent,
+            gpu_core,
+            memory,
+            platform_controller_hub,
+            dc_in,
+            rail_12v,
+        })
+    }
+
+    /// Returns the overall [`Currents`] information available.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn currents(&mut self) -> Result<Currents> {
+        let cpu_core = self.read("current.cpu_core")?;
+        let cpu_rail = self.read("current.cpu_rail")?;
+        let cpu_vccio = self.read("current.cpu_vccio")?;
+        let cpu_system_agent = self.read("current.cpu_system_agent")?;
+        let gpu_core = self.read("current.gpu_core")?;
+        let memory = self.read("current.memory")?;
+        let dc_in = self.read("current.dc_in")?;
+        let charger = self.read("current.charger")?;
+        Ok(Currents {
+            cpu_core,
+            cpu_rail,
+            cpu_vccio,
+            cpu_system_agent,
+            gpu_core,
+            memory,
+            dc_in,
+            charger,
+        })
+    }
+
+    /// Returns the [`SystemInfo`] that identifies the SMC firmware and platform.
+    /// It does not change while the system is running, so it is only read once.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn system_info(&mut self) -> Result<SystemInfo> {
+        if let Some(info) = &self.system_info {
+            return Ok(info.clone());
+        }
+        let revision = self.read("system.revision")?;
+        let interface_version = self.inner.version()?;
+        let branch = self.read("system.branch")?;
+        let platform_id = self.read("system.platform_id")?;
+        let info = SystemInfo {
+            platform: self.platform,
+            revision,
+            interface_version,
+            branch,
+            platform_id,
+        };
+        self.system_info = Some(info.clone());
+        Ok(info)
+    }
+
+    /// Returns the readings of the [`AmbientLight`] sensors.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn ambient_light(&mut self) -> Result<AmbientLight> {
+        let left = self.read("light.left")?;
+        let right = self.read("light.right")?;
+        Ok(AmbientLight { left, right })
+    }
+
+    /// Returns true if the lid is closed, e.g. when running in clamshell mode.
+    /// If the sensor is missing, the lid is reported as open.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn is_lid_closed(&mut self) -> Result<bool> {
+        self.read("lid.closed")
+    }
+
+    /// Returns the [`Motion`] as reported by the sudden motion sensor.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn motion(&mut self) -> Result<Motion> {
+        let x = self.read("motion.x")?;
+        let y = self.read("motion.y")?;
+        let z = self.read("motion.z")?;
+        Ok(Motion { x, y, z })
+    }
+
+    /// Returns the level of the keyboard backlight.
+    /// This is only available on Intel models, if the key is missing, the value is 0.0
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn keyboard_backlight(&mut self) -> Result<Percent> {
+        self.read("keyboard.backlight")
+    }
+
+    /// Sets the level of the keyboard backlight.
+    /// Values outside of 0.0 to 100.0 are clamped into that range.
+    ///
+    /// # Errors
+    /// [`Error::KeyNotAvailable`] If the keyboard backlight is not available
+    ///
+    /// [`Error::DataError`] If the value could not be written
+    ///
+    /// [`Error::InsufficientPrivileges`] If writing to the SMC requires `sudo`
+    pub fn set_keyboard_backlight(&mut self, level: Percent) -> Result<()> {
+        self.write("keyboard.backlight", level)
+    }
+
+    /// Returns the [`PowerLimits`] that the SMC currently applies.
+    /// Use this to detect thermal throttling of the CPU or GPU.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn power_limits(&mut self) -> Result<PowerLimits> {
+        Ok(self.inner.power_limits()?)
+    }
+
+    /// Returns the overall [`ThermalStatus`] of this machine,
+    /// using the [`ThresholdPolicy`] of this SMC.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # fn main() -> Result<()> {
+    /// let mut smc = Smc::connect()?;
+    /// let status = smc.thermal_status()?;
+    /// if status.state >= ThermalState::Serious {
+    ///     println!("Running hot: {:?}", status.cause);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn thermal_status(&mut self) -> Result<ThermalStatus> {
+        let cpu = self.cpu_temperature()?;
+        let gpu = self.gpu_temperature()?;
+        let fans = self.fans()?.collect::<Result<Vec<_>>>()?;
+        let limits = self.power_limits()?;
+        Ok(ThermalStatus::classify(&cpu, &gpu, &fans, &limits, |id| {
+            self.temperature_thresholds(id)
+        }))
+    }
+
+    /// Reads all sensor groups and returns them as one [`SmcSnapshot`].
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn snapshot(&mut self) -> Result<SmcSnapshot> {
+        self.read_snapshot(&SensorGroup::ALL, SystemTime::now())
+    }
+
+    /// Reads the given sensor groups and returns them as one [`SmcSnapshot`].
+    /// All other fields of the snapshot are 0.0 or empty.
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # fn main() -> Result<()> {
+    /// let mut smc = Smc::connect()?;
+    /// let snapshot = smc.snapshot_of(&[SensorGroup::Fans])?;
+    /// assert_eq!(*snapshot.cpu_temperature.proximity, 0.0);
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn snapshot_of(&mut self, groups: &[SensorGroup]) -> Result<SmcSnapshot> {
+        self.read_snapshot(groups, SystemTime::now())
+    }
+
+    fn read_snapshot(
+        &mut self,
+        groups: &[SensorGroup],
+        timestamp: SystemTime,
+    ) -> Result<SmcSnapshot> {
+        let mut snapshot = SmcSnapshot::new(timestamp, self.system_info()?);
+        for group in groups {
+            match group {
+                SensorGroup::Cpu => snapshot.cpu_temperature = self.cpu_temperature()?,
+                SensorGroup::CpuCores => {
+                    snapshot.cpu_cores = self.cpu_core_temps()?.collect::<Result<_>>()?;
+                }
+                SensorGroup::Gpu => {
+                    snapshot.gpu_temperature = self.gpu_temperature()?;
+                    snapshot.gpus = self.gpus()?.collect::<Result<_>>()?;
+                }
+                SensorGroup::Other => {
+                    snapshot.other_temperatures = self.other_temperatures()?;
+                    snapshot.dimm_temperatures =
+                        self.dimm_temperatures()?.collect::<Result<_>>()?;
+                    snapshot.drive_temperatures =
+                        self.drive_temperatures()?.collect::<Result<_>>()?;
+                }
+                SensorGroup::Fans => snapshot.fans = self.fans()?.collect::<Result<_>>()?,
+                SensorGroup::Battery => {
+                    snapshot.battery_info = self.battery_info()?;
+                    snapshot.batteries = self.battery_details()?.collect::<Result<_>>()?;
+                }
+                SensorGroup::Power => {
+                    snapshot.cpu_power = self.cpu_power()?;
+                    snapshot.gpu_power = self.gpu_power()?;
+                    snapshot.power_dc_in = self.power_dc_in()?;
+                    snapshot.power_system_total = self.power_system_total()?;
+                    snapshot.power_limits = self.power_limits()?;
+                }
+                SensorGroup::Voltages => snapshot.voltages = self.voltages()?,
+                SensorGroup::Currents => snapshot.currents = self.currents()?,
+                SensorGroup::Environment => {
+                    snapshot.ambient_light = self.ambient_light()?;
+                    snapshot.lid_closed = self.is_lid_closed()?;
+                    snapshot.motion = self.motion()?;
+                    snapshot.keyboard_backlight = self.keyboard_backlight()?;
+                }
+            }
+        }
+        Ok(snapshot)
+    }
+
+    /// Returns the current value of every sensor in the [`Catalog`] that is available
+    /// on this machine, in the order of the catalog.
+    /// Keys that are not a number, e.g. the firmware version, are skipped.
+    ///
+    /// Every value is read from the SMC, which takes a while for the whole catalog.
+    /// The [`Quality`] of every value is checked against the values of previous calls,
+    /// see [`Plausibility`].
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn sensors(&mut self) -> Result<Vec<Sensor>> {
+        let entries = self
+            .catalog
+            .entries()
+            .iter()
+            .filter(|entry| entry.applies_to(self.platform, &self.model))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let mut seen = HashSet::new();
+        let mut numbers = HashMap::new();
+        let mut sensors = Vec::new();
+        for entry in entries {
+            for index in 0..=9 {
+                let key = match entry.command_key(index) {
+                    Some(key) => key,
+                    None => break,
+                };
+                // the first entry wins for keys that are used by several ids
+                if !seen.insert(key.0) {
+                    continue;
+                }
+                let value = match entry.unit.read(&mut self.inner, key)? {
+                    Some(value) => value,
+                    None => continue,
+                };
+                let number = if entry.is_indexed() {
+                    usize::from(index) + 1
+                } else {
+                    let number = numbers.entry(entry.id.clone()).or_insert(0);
+                    *number += 1;
+                    *number
+                };
+                sensors.push(Sensor {
+                    key: String::from_utf8_lossy(&key.to_be_bytes()).into_owned(),
+                    id: entry.id.clone(),
+                    label: entry.numbered_label(number),
+                    kind: entry.category,
+                    unit: entry.unit,
+                    value,
+                    quality: Quality::Good,
+                });
+            }
+        }
+        self.plausibility
+            .check_sensors(&mut sensors, Instant::now());
+        Ok(sensors)
+    }
+
+    /// Returns every temperature, fan, power, voltage and current sensor that is available,
+    /// in the order of their keys.
+    ///
+    /// Unlike the other methods, this does not only look at the keys in the [`Catalog`],
+    /// but walks all keys of the SMC and classifies them by their prefix and data type.
+    /// Sensors that are not in the catalog get a placeholder label.
+    /// Walking all keys takes considerably longer than the other methods.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn discover(&mut self) -> Result<Vec<DiscoveredSensor>> {
+        let keys = self
+            .all_keys()?
+            .filter(|info| !matches!(info, Err(Error::DataError { .. })))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Keys that are listed one by one are numbered in the order they are found,
+        // which is also the order of the typed getters.
+        let mut numbers = HashMap::new();
+        let mut sensors = Vec::new();
+        for DbgKeyInfo { key, data_type, .. } in keys {
+            let reading = match Reading::read(&mut self.inner, &key, &data_type)? {
+                Some(reading) => reading,
+                None => continue,
+            };
+            let (label, known) = match self.catalog.entry_for_key(&key, self.platform, &self.model)
+            {
+                Some(entry) if entry.is_indexed() => {
+                    let index = entry.index_of(&key).unwrap_or_default();
+                    (entry.numbered_label(usize::from(index) + 1), true)
+                }
+                Some(entry) => {
+                    let number = numbers.entry(entry.id.clone()).or_insert(0);
+                    *number += 1;
+                    (entry.numbered_label(*number), true)
+                }
+                None => (format!("{} {}", reading.name(), key.trim_end()), false),
+            };
+            sensors.push(DiscoveredSensor {
+                key,
+                label,
+                known,
+                reading,
+            });
+        }
+        Ok(sensors)
+    }
+
+    /// Returns the number of available keys to query.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn number_of_keys(&mut self) -> Result<u32> {
+        Ok(self.inner.read_value(ReadKey::new(NUMBER_OF_KEYS))?)
+    }
+
+    /// Returns an iterator over the available keys.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn all_keys(&mut self) -> Result<KeysIter> {
+        KeysIter::new(self)
+    }
+
+    /// Returns an iterator over the available data points.
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn all_data(&mut self) -> Result<DataIter> {
+        DataIter::new(self)
+    }
+
+    fn key_info_by_index(&mut self, index: u32) -> Result<DbgKeyInfo> {
+        let info = self.inner.key_info_by_index(index)?;
+        let key = info.key.to_be_bytes();
+        let key = std::str::from_utf8(&key).map_err(|_| InternalError::DataError {
+            key: info.key,
+            tpe: info.data_type,
+        })?;
+        self.key_info(key)
+    }
+
+    fn key_data_by_index(&mut self, index: u32) -> Result<Dbg> {
+        let info = self.inner.key_info_by_index(index)?;
+        let key = info.key.to_be_bytes();
+        let key = std::str::from_utf8(&key).map_err(|_| InternalError::DataError {
+            key: info.key,
+            tpe: info.data_type,
+        })?;
+        Ok(self.check(key))
+    }
+
+    fn key_info(&mut self, name: &str) -> Result<DbgKeyInfo> {
+        let info = self.inner.key_info(Check(name))?;
+        let key = info.key.to_be_bytes();
+        let tpe = info.data_type.to_be_bytes();
+
+        Ok(DbgKeyInfo {
+            key: String::from_utf8_lossy(&key).to_string(),
+            data_type: String::from_utf8_lossy(&tpe).to_string(),
+            data_size: info.data_size.try_into().unwrap_or(usize::max_value()),
+        })
+    }
+
+    fn check(&mut self, name: &str) -> Dbg {
+        let value = self.inner.opt_read_value(Check(name));
+        Dbg {
+            key: name.to_string(),
+            value: value.map_err(Error::from),
+        }
+    }
+}
+
+/// Calls the given macro with all methods of [`Smc`] that [`SharedSmc`] and `AsyncSmc`
+/// forward to, so that a new method can't be forgotten in one of them.
+/// A method is given by its arguments, the value of its [`Result`],
+/// and a function that calls it on the [`Smc`].
+macro_rules! smc_methods {
+    ($forward:ident) => {
+        $forward! {
+            fn fans() -> Vec<FanSpeed> = |smc| smc.fans()?.collect();
+            fn battery_info() -> BatteryInfo = Smc::battery_info;
+            fn battery_details() -> Vec<BatteryDetail> = |smc| smc.battery_details()?.collect();
+            fn cpu_temperature() -> CpuTemperatures = Smc::cpu_temperature;
+            #[cfg(any(doc, target_os = "macos"))]
+            fn cpu_core_temps() -> Vec<CoreTemperature> = |smc| smc.cpu_core_temps()?.collect();
+            fn gpu_temperature() -> GpuTemperatures = Smc::gpu_temperature;
+            fn gpus() -> Vec<GpuDetail> = |smc| smc.gpus()?.collect();
+            fn other_temperatures() -> OtherTemperatures = Smc::other_temperatures;
+            fn dimm_temperatures() -> Vec<DimmTemperatures> =
+                |smc| smc.dimm_temperatures()?.collect();
+            fn drive_temperatures() -> Vec<DriveTemperatures> =
+                |smc| smc.drive_temperatures()?.collect();
+            fn cpu_power() -> CpuPower = Smc::cpu_power;
+            fn gpu_power() -> Watt = Smc::gpu_power;
+            fn package_with_gpu_power() -> Option<Watt> = Smc::package_with_gpu_power;
+            fn power_dc_in() -> Watt = Smc::power_dc_in;
+            fn power_system_total() -> Watt = Smc::power_system_total;
+            fn voltages() -> Voltages = Smc::voltages;
+            fn currents() -> Currents = Smc::currents;
+            fn system_info() -> SystemInfo = Smc::system_info;
+            fn ambient_light() -> AmbientLight = Smc::ambient_light;
+            fn is_lid_closed() -> bool = Smc::is_lid_closed;
+            fn motion() -> Motion = Smc::motion;
+            fn keyboard_backlight() -> Percent = Smc::keyboard_backlight;
+            fn set_keyboard_backlight(level: Percent) -> () =
+                move |smc| smc.set_keyboard_backlight(level);
+            fn power_limits() -> PowerLimits = Smc::power_limits;
+            fn thermal_status() -> ThermalStatus = Smc::thermal_status;
+            fn snapshot() -> SmcSnapshot = Smc::snapshot;
+            fn snapshot_of(groups: &[SensorGroup]) -> SmcSnapshot = {
+                let groups = groups.to_vec();
+                move |smc| smc.snapshot_of(&groups)
+            };
+            fn sensors() -> Vec<Sensor> = Smc::sensors;
+            fn discover() -> Vec<DiscoveredSensor> = Smc::discover;
+        }
+    };
+}
+
+macro_rules! shared_methods {
+    ($( $(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) -> $out:ty = $call:expr; )*) => {
+        $(
+            #[doc = concat!("See [`Smc::", stringify!($name), "`]")]
+            ///
+            /// # Errors
+            #[doc = concat!("The errors of [`Smc::", stringify!($name), "`]")]
+            $(#[$meta])*
+            pub fn $name(&self, $($arg: $ty),*) -> Result<$out> {
+                self.with($call)
+            }
+        )*
+    };
+}
+
+/// A handle to the SMC that can be shared between threads.
+///
+/// All clones of the handle share one connection, and calls from different threads
+/// are run one after another. Unlike [`Smc`], all methods take `&self`.
+/// The connection is closed once all clones are dropped.
+///
+/// Use [`SharedSmc::with`] for a sequence of calls that must not be interleaved
+/// with calls of other threads, or for methods that have no shared version.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # use std::thread;
+/// # fn main() -> Result<()> {
+/// let smc = SharedSmc::connect()?;
+/// let fans = {
+///     let smc = smc.clone();
+///     thread::spawn(move || smc.fans())
+/// };
+/// let cpu_temp = smc.cpu_temperature()?;
+/// assert!(*cpu_temp.proximity > 0.0);
+/// let fans = fans.join().unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(doc, doc(cfg(target_os = "macos")))]
+#[derive(Clone, Debug)]
+pub struct SharedSmc {
+    smc: Arc<Mutex<Smc>>,
+}
+
+impl SharedSmc {
+    #![cfg_attr(doc, doc(cfg(target_os = "macos")))]
+
+    /// Creates a new connection to the SMC system.
+    ///
+    /// # Errors
+    /// [`Error::SmcNotAvailable`] If the SMC system is not available
+    pub fn connect() -> Result<Self> {
+        Smc::connect().map(Self::new)
+    }
+
+    /// Shares an existing connection, e.g. after its catalog was extended.
+    pub fn new(smc: Smc) -> Self {
+        Self {
+            smc: Arc::new(Mutex::new(smc)),
+        }
+    }
+
+    /// Runs the given function with exclusive access to the [`Smc`] and returns its result.
+    /// Other threads wait until the function returns.
+    pub fn with<T>(&self, f: impl FnOnce(&mut Smc) -> T) -> T {
+        // the Smc keeps no state that a panic could leave inconsistent
+        f(&mut self.smc.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+
+    /// See [`Smc::platform`]
+    pub fn platform(&self) -> Platform {
+        self.with(|smc| smc.platform())
+    }
+
+    smc_methods!(shared_methods);
+}
+
+impl From<Smc> for SharedSmc {
+    fn from(smc: Smc) -> Self {
+        Self::new(smc)
+    }
+}
+
+/// The source of time for a [`Sampler`].
+/// Implement this to control the sampler in tests, e.g. with a clock that only advances
+/// when the test asks for the next sample.
+pub trait Clock: fmt::Debug + Send + 'static {
+    /// Returns the current time, which is used as the timestamp of a sample.
+    fn now(&mut self) -> SystemTime;
+
+    /// Blocks the sampler thread for the given duration.
+    /// This must return early once `stop` receives a message or is disconnected,
+    /// which happens when the sampler is stopped.
+    fn sleep(&mut self, duration: Duration, stop: &mpsc::Receiver<()>);
+}
+
+/// The [`Clock`] of the operating system.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&mut self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&mut self, duration: Duration, stop: &mpsc::Receiver<()>) {
+        let _ = stop.recv_timeout(duration);
+    }
+}
+
+/// The receiving end of a [`Sampler`] subscription.
+/// It is disconnected once the sampler is stopped.
+pub type Subscription = mpsc::Receiver<Result<SmcSnapshot>>;
+
+/// Configures and starts a [`Sampler`].
+#[derive(Debug)]
+pub struct SamplerBuilder<C = SystemClock> {
+    interval: Duration,
+    groups: Vec<SensorGroup>,
+    clock: C,
+    subscribers: Vec<mpsc::Sender<Result<SmcSnapshot>>>,
+}
+
+impl SamplerBuilder {
+    fn new() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            groups: SensorGroup::ALL.to_vec(),
+            clock: SystemClock,
+            subscribers: Vec::new(),
+        }
+    }
+}
+
+impl<C: Clock> SamplerBuilder<C> {
+    /// Sets the time between two samples, the default is 1 second.
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets the sensors that are read for every sample, the default are all [`SensorGroup`]s.
+    pub fn groups(mut self, groups: &[SensorGroup]) -> Self {
+        self.groups = groups.to_vec();
+        self
+    }
+
+    /// Sets the [`Clock`] that timestamps the samples and waits between them.
+    pub fn clock<D: Clock>(self, clock: D) -> SamplerBuilder<D> {
+        SamplerBuilder {
+            interval: self.interval,
+            groups: self.groups,
+            clock,
+            subscribers: self.subscribers,
+        }
+    }
+
+    /// Subscribes to the samples, including the very first one.
+    /// Use [`Sampler::subscribe`] to subscribe after the sampler was started.
+    pub fn subscribe(&mut self) -> Subscription {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Starts the sampler on a new thread, that connects to the SMC with the given function
+    /// and owns the connection until the sampler is stopped.
+    ///
+    /// # Errors
+    /// Any error of the `connect` function, e.g. [`Error::SmcNotAvailable`]
+    pub fn spawn<F>(self, connect: F) -> Result<Sampler>
+    where
+        F: FnOnce() -> Result<Smc> + Send + 'static,
+    {
+        let shared = Arc::new(SamplerState {
+            stopped: AtomicBool::new(false),
+            subscribers: Mutex::new(self.subscribers),
+        });
+        let (ready, connected) = mpsc::sync_channel(1);
+        let (stop, stopping) = mpsc::channel();
+        let state = Arc::clone(&shared);
+        let SamplerBuilder {
+            interval,
+            groups,
+            mut clock,
+            ..
+        } = self;
+        let handle = thread::spawn(move || {
+            let mut smc = match connect() {
+                Ok(smc) => {
+                    let _ = ready.send(Ok(()));
+                    smc
+                }
+                Err(e) => {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+            };
+            while !state.stopped.load(Ordering::Acquire) {
+                let timestamp = clock.now();
+                let sample = smc.read_snapshot(&groups, timestamp);
+                state
+                    .subscribers
+                    .lock()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .retain(|subscriber| subscriber.send(sample.clone()).is_ok());
+                if state.stopped.load(Ordering::Acquire) {
+                    break;
+                }
+                let elapsed = clock.now().duration_since(timestamp).unwrap_or_default();
+                clock.sleep(interval.checked_sub(elapsed).unwrap_or_default(), &stopping);
+            }
+        });
+        match connected.recv() {
+            Ok(Ok(())) => Ok(Sampler {
+                state: shared,
+                stop,
+                handle: Some(handle),
+            }),
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e)
+            }
+            Err(_) => Err(Error::SmcNotAvailable),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct SamplerState {
+    stopped: AtomicBool,
+    subscribers: Mutex<Vec<mpsc::Sender<Result<SmcSnapshot>>>>,
+}
+
+/// Reads sensors on a background thread and sends the samples to any number of subscribers.
+///
+/// The sampler owns its SMC connection, so that the subscribers do not need
+/// exclusive access to an [`Smc`]. Every sample is an [`SmcSnapshot`] in which only
+/// the configured [`SensorGroup`]s are filled in, or the error that happened while reading them.
+/// Subscribers that drop their [`Subscription`] are removed.
+///
+/// The sampler stops when [`Sampler::stop`] is called or when it is dropped,
+/// which disconnects all subscriptions.
+///
+/// # Examples
+/// ```
+/// # use macsmc::*;
+/// # use std::time::Duration;
+/// # fn main() -> Result<()> {
+/// let mut builder = Sampler::builder()
+///     .interval(Duration::from_millis(500))
+///     .groups(&[SensorGroup::Cpu, SensorGroup::Fans]);
+/// let samples = builder.subscribe();
+/// let sampler = builder.spawn(Smc::connect)?;
+///
+/// for sample in samples.iter().take(3) {
+///     let sample = sample?;
+///     println!("{:?}: {}", sample.timestamp, sample.cpu_temperature.proximity);
+/// }
+/// sampler.stop();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sampler {
+    state: Arc<SamplerState>,
+    stop: mpsc::Sender<()>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Sampler {
+    /// Returns a builder to configure and start a sampler.
+    pub fn builder() -> SamplerBuilder {
+        SamplerBuilder::new()
+    }
+
+    /// Subscribes to all samples that are taken from now on.
+    pub fn subscribe(&self) -> Subscription {
+        let (sender, receiver) = mpsc::channel();
+        self.state
+            .subscribers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(sender);
+        receiver
+    }
+
+    /// Stops the sampler and waits for its thread to finish.
+    /// A sample that is currently being read is still sent to the subscribers.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            self.state.stopped.store(true, Ordering::Release);
+            let _ = self.stop.send(());
+            let _ = handle.join();
+            self.state
+                .subscribers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clear();
+        }
+    }
+}
+
+impl Drop for Sampler {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+#[cfg(feature = "tokio")]
+macro_rules! async_methods {
+    ($( $(#[$meta:meta])* fn $name:ident($($arg:ident: $ty:ty),*) -> $out:ty = $call:expr; )*) => {
+        $(
+            #[doc = concat!("See [`Smc::", stringify!($name), "`]")]
+            ///
+            /// # Errors
+            #[doc = concat!(
+                "The errors of [`Smc::",
+                stringify!($name),
+                "`], or [`Error::SmcNotAvailable`] if the SMC thread stopped"
+            )]
+            $(#[$meta])*
+            pub async fn $name(&self, $($arg: $ty),*) -> Result<$out> {
+                self.call($call).await
+            }
+        )*
+    };
+}
+
+/// Async access to the SMC for tokio based applications.
+#[cfg(feature = "tokio")]
+mod async_smc {
+    use super::*;
+    use futures_core::Stream;
+    use std::{
+        future::Future,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+    use tokio::{sync::oneshot, time};
+
+    enum Message {
+        Call(Box<dyn FnOnce(&mut Smc) + Send>),
+        Shutdown(oneshot::Sender<()>),
+    }
+
+    /// An async handle to the SMC, for use in tokio based applications.
+    ///
+    /// The connection is owned by a dedicated thread, which runs all SMC calls,
+    /// so that the blocking calls do not stall the async runtime.
+    /// The handle can be cloned, all clones share the same connection and their calls
+    /// are run one after another. The thread stops once all clones and streams are dropped.
+    ///
+    /// Dropping the future of a call before it completes is safe, the call still runs
+    /// on the SMC thread but its result is discarded. The same is true for [`AsyncSmc::shutdown`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # use std::time::Duration;
+    /// # async fn run() -> Result<()> {
+    /// let smc = AsyncSmc::connect().await?;
+    /// let cpu_temp = smc.cpu_temperature().await?;
+    ///
+    /// let mut readings = smc.interval_stream(Duration::from_secs(1), &[SensorGroup::Fans]);
+    /// while let Some(reading) = readings.next().await {
+    ///     println!("{:?}", reading?.fans);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(doc, doc(cfg(feature = "tokio")))]
+    #[derive(Clone, Debug)]
+    pub struct AsyncSmc {
+        messages: mpsc::Sender<Message>,
+    }
+
+    impl AsyncSmc {
+        /// Connects to the SMC on a new thread.
+        ///
+        /// # Errors
+        /// [`Error::SmcNotAvailable`] If the SMC system is not available
+        pub async fn connect() -> Result<Self> {
+            Self::spawn(Smc::connect).await
+        }
+
+        /// Starts a new thread, that connects to the SMC with the given function
+        /// and owns the connection until all handles are dropped.
+        ///
+        /// # Errors
+        /// Any error of the `connect` function, e.g. [`Error::SmcNotAvailable`]
+        pub async fn spawn<F>(connect: F) -> Result<Self>
+        where
+            F: FnOnce() -> Result<Smc> + Send + 'static,
+        {
+            let (messages, queue) = mpsc::channel();
+            let (ready, connected) = oneshot::channel();
+            let _ = thread::spawn(move || {
+                let mut smc = match connect() {
+                    Ok(smc) => {
+                        let _ = ready.send(Ok(()));
+                        smc
+                    }
+                    Err(e) => {
+                        let _ = ready.send(Err(e));
+                        return;
+                    }
+                };
+                for message in queue {
+                    match message {
+                        Message::Call(call) => call(&mut smc),
+                        Message::Shutdown(done) => {
+                            drop(smc);
+                            let _ = done.send(());
+                            return;
+                        }
+                    }
+                }
+            });
+            match connected.await {
+                Ok(Ok(())) => Ok(Self { messages }),
+                Ok(Err(e)) => Err(e),
+                Err(_) => Err(Error::SmcNotAvailable),
+            }
+        }
+
+        /// Runs the given function with the [`Smc`] on the SMC thread and returns its result.
+        /// Use this for methods that have no async version, e.g. [`Smc::extend_catalog`].
+        ///
+        /// # Errors
+        /// Any error of the function, or [`Error::SmcNotAvailable`] if the SMC thread stopped
+        pub async fn call<T, F>(&self, f: F) -> Result<T>
+        where
+            F: FnOnce(&mut Smc) -> Result<T> + Send + 'static,
+            T: Send + 'static,
+        {
+            self.submit(f).await.unwrap_or(Err(Error::SmcNotAvailable))
+        }
+
+        fn submit<T, F>(&self, f: F) -> oneshot::Receiver<Result<T>>
+        where
+            F: FnOnce(&mut Smc) -> Result<T> + Send + 'static,
+            T: Send + 'static,
+        {
+            let (result, receiver) = oneshot::channel();
+            // if the thread is gone, the result sender is dropped and the receiver fails
+            let _ = self.messages.send(Message::Call(Box::new(move |smc| {
+                let _ = result.send(f(smc));
+            })));
+            receiver
+        }
+
+        /// Disconnects from the SMC once all calls that were made so far are finished,
+        /// and waits until the SMC thread has stopped.
+        /// Any later calls of other clones of this handle fail with [`Error::SmcNotAvailable`],
+        /// and all streams end.
+        ///
+        /// The shutdown is requested right away, the returned future only waits for it.
+        /// The SMC thread is stopped even if the future is dropped without being polled.
+        pub fn shutdown(self) -> impl Future<Output = ()> {
+            let (done, stopped) = oneshot::channel();
+            // if the thread is gone, `done` is dropped and the future completes immediately
+            let _ = self.messages.send(Message::Shutdown(done));
+            async move {
+                let _ = stopped.await;
+            }
+        }
+
+        /// Returns a stream that reads the given sensor groups every `period`,
+        /// see [`Smc::snapshot_of`]. The first reading is taken immediately.
+        /// Readings that take longer than the period delay the next ones instead of piling up.
+        ///
+        /// The stream ends if the SMC thread stops. Dropping the stream stops the readings.
+        ///
+        /// # Panics
+        /// If `period` is zero, or if this is not called within a tokio runtime with time enabled
+        pub fn interval_stream(&self, period: Duration, groups: &[SensorGroup]) -> SnapshotStream {
+            let mut interval = time::interval(period);
+            interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+            SnapshotStream {
+                smc: self.clone(),
+                groups: groups.into(),
+                interval,
+                pending: None,
+            }
+        }
+
+        smc_methods!(async_methods);
+    }
+
+    /// A stream of [`SmcSnapshot`]s, as returned by [`AsyncSmc::interval_stream`].
+    #[cfg_attr(doc, doc(cfg(feature = "tokio")))]
+    #[derive(Debug)]
+    pub struct SnapshotStream {
+        smc: AsyncSmc,
+        groups: Arc<[SensorGroup]>,
+        interval: time::Interval,
+        pending: Option<oneshot::Receiver<Result<SmcSnapshot>>>,
+    }
+
+    impl SnapshotStream {
+        /// Waits for the next reading.
+        /// Returns None if the SMC thread stopped.
+        ///
+        /// This is cancellation safe, a reading that is dropped while it is being taken
+        /// is returned by the next call instead.
+        pub async fn next(&mut self) -> Option<Result<SmcSnapshot>> {
+            std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+        }
+    }
+
+    impl Stream for SnapshotStream {
+        type Item = Result<SmcSnapshot>;
+
+        fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            let this = self.get_mut();
+            loop {
+                if let Some(pending) = &mut this.pending {
+                    let reading = match Pin::new(pending).poll(cx) {
+                        Poll::Ready(reading) => reading,
+                        Poll::Pending => return Poll::Pending,
+                    };
+                    this.pending = None;
+                    return Poll::Ready(reading.ok());
+                }
+                if this.interval.poll_tick(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                let groups = Arc::clone(&this.groups);
+                this.pending = Some(this.smc.submit(move |smc| smc.snapshot_of(&groups)));
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use async_smc::{AsyncSmc, SnapshotStream};
+
+macro_rules! iter_impl {
+    ( $(#[$meta:meta])*
+    $struct:ident($range:tt) = $max:ident : $get:ident -> $out:tt) => {
+        $(#[$meta])*
+        /// Advancing this iterator by calling `nth` is a O(1) operation
+        /// and will not query all intermediate keys.
+        #[derive(Debug)]
+        pub struct $struct<'a> {
+            smc: &'a mut $crate::Smc,
+            next: $range,
+            max: $range,
+        }
+
+        impl<'a> $struct<'a> {
+            fn new(smc: &'a mut $crate::Smc) -> $crate::Result<Self> {
+                let max = $range::from(smc.$max()?);
+                Ok(Self { smc, next: 0, max })
+            }
+        }
+
+        impl<'a> Iterator for $struct<'a> {
+            type Item = $crate::Result<$out>;
+
+            fn next(&mut self) -> Option<Self::Item> {
+                if self.next >= self.max {
+                    return None;
+                }
+                let value = match self.smc.$get(self.next) {
+                    Ok(value) => value,
+                    Err(e) => return Some(Err(e)),
+                };
+                self.next += 1;
+                Some(Ok(value))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let items_left = (self.max - self.next) as usize;
+                (items_left, Some(items_left))
+            }
+
+            fn count(self) -> usize {
+                (self.max - self.next) as usize
+            }
+
+            fn last(mut self) -> Option<Self::Item> {
+                self.next = self.next.max(self.max.saturating_sub(1));
+                self.next()
+            }
+
+            fn nth(&mut self, n: usize) -> Option<Self::Item> {
+                self.next = (self.next as usize).saturating_add(n) as $range;
+                self.next()
+            }
+        }
+
+        impl<'a> DoubleEndedIterator for $struct<'a> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                if self.max <= self.next {
+                    return None;
+                }
+                self.max -= 1;
+                Some(self.smc.$get(self.max))
+            }
+
+            fn nth_back(&mut self, n: usize) -> Option<Self::Item> {
+                self.max = (self.max as usize).saturating_sub(n) as $range;
+                self.next_back()
+            }
+        }
+    };
+}
+
+iter_impl! {
+    /// Iterator for [`FanSpeed`]s.
+    FanIter(u8) = number_of_fans: fan_speed -> FanSpeed
+}
+
+impl FanIter<'_> {
+    /// Consumes the iterator and returns the average speed of all fans,
+    /// see [`FanSpeed::average_percentage`].
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn average_percentage(self) -> Result<Option<f32>> {
+        let fans = self.collect::<Result<Vec<_>>>()?;
+        Ok(FanSpeed::average_percentage(&fans))
+    }
+}
+
+iter_impl! {
+    /// Iterator for [`BatteryDetail`]s.
+    BatteryIter(u8) = number_of_batteries: battery_detail -> BatteryDetail
+}
+
+iter_impl! {
+    /// Iterator for [`GpuDetail`]s.
+    GpuIter(u8) = number_of_gpus: gpu_detail -> GpuDetail
+}
+
+iter_impl! {
+    /// Iterator for [`DimmTemperatures`]s.
+    DimmIter(u8) = number_of_dimms: dimm_temperature -> DimmTemperatures
+}
+
+iter_impl! {
+    /// Iterator for [`DriveTemperatures`]s.
+    DriveIter(u8) = number_of_drives: drive_temperature -> DriveTemperatures
+}
+
+#[cfg(any(doc, target_os = "macos"))]
+iter_impl! {
+    /// Iterator for the [`CoreTemperature`]s of all cpu cores.
+    CpuIter(u8) = number_of_cpus: cpu_core_temperature -> CoreTemperature
+}
+
+#[cfg(any(doc, target_os = "macos"))]
+impl CpuIter<'_> {
+    /// Consumes the iterator and aggregates the temperatures of all cores,
+    /// see [`CoreTemperatureStats::of`].
+    ///
+    /// # Examples
+    /// ```
+    /// # use macsmc::*;
+    /// # fn main() -> Result<()> {
+    /// let mut smc = Smc::connect()?;
+    /// if let Some(stats) = smc.cpu_core_temps()?.stats()? {
+    ///     println!("hottest core: {}, average: {}", stats.max, stats.mean);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    /// [`Error::DataError`] If there was something wrong while getting the data
+    pub fn stats(self) -> Result<Option<CoreTemperatureStats>> {
+        let cores = self.collect::<Result<Vec<_>>>()?;
+        Ok(CoreTemperatureStats::of(&cores))
+    }
+}
+
+iter_impl! {
+    /// Iterator for all [`DbgKeyInfo`]s.
+    KeysIter(u32) = number_of_keys: key_info_by_index -> DbgKeyInfo
+}
+
+iter_impl! {
+    /// Iterator for all [`Dbg`]s.
+    DataIter(u32) = number_of_keys: key_data_by_index -> Dbg
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct CommandKey(u32);
+
+impl Deref for CommandKey {
+    type Target = u32;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+trait ReadAction {
+    type Out: ValueParser;
+
+    fn key(&self) -> CommandKey;
+
+    fn parse(self, val: DataValue) -> InternalResult<Self::Out>
+    where
+        Self: Sized,
+    {
+        <Self::Out as ValueParser>::parse(val)
+    }
+}
+
+trait ValueParser: Sized {
+    fn parse(val: DataValue) -> InternalResult<Self>;
+}
+
+impl ValueParser for Celsius {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(value) => Ok(Self(value)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for Rpm {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(value) => Ok(Self(value)),
+            DataValue::Uint(v) => Ok(Self(f32::from(u16::try_from(v)?))),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for FanMode {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Flag(bool) => Ok(bool.into()),
+            DataValue::Int(value) => Ok((value != 0).into()),
+            DataValue::Uint(value) => Ok((value != 0).into()),
+            DataValue::Float(value) => Ok((value != 0.0).into()),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct BatteryStatus {
+    charging: bool,
+    ac_present: bool,
+    health_ok: bool,
+}
+
+impl ValueParser for BatteryStatus {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Uint(val) => {
+                let charging = val & 0x01 == 0x01;
+                let ac_present = val & 0x02 == 0x02;
+                let health_ok = val & 0x40 == 0x40;
+                Ok(BatteryStatus {
+                    charging,
+                    ac_present,
+                    health_ok,
+                })
+            }
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for MilliAmpereHours {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Uint(v) => Ok(Self(v.try_into()?)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for MilliAmpere {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Int(v) => Ok(Self(v.try_into()?)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for Watt {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(v) => Ok(Self(v)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for Volt {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(v) => Ok(Self(v)),
+            DataValue::Uint(v) => Ok(Self(f32::from(u16::try_from(v)?) / 1000.0)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for Ampere {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(v) => Ok(Self(v)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for LightSensor {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Unknown(data) if data.len() >= 6 => Ok(LightSensor {
+                valid: data[0] != 0,
+                high_gain: data[1] != 0,
+                broadband: u16::from_be_bytes(data[2..4].try_into()?),
+                infrared: u16::from_be_bytes(data[4..6].try_into()?),
+            }),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for f32 {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Float(v) => Ok(v),
+            DataValue::Int(v) => Ok(f32::from(i16::try_from(v)?)),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for Percent {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Unknown(data) if !data.is_empty() => {
+                Ok(Self(f32::from(data[0]) * 100.0 / f32::from(u8::MAX)))
+            }
+            DataValue::Uint(v) => Ok(Self(
+                f32::from(u8::try_from(v)?) * 100.0 / f32::from(u8::MAX),
+            )),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for bool {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Flag(v) => Ok(v),
+            DataValue::Uint(v) => Ok(v != 0),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for u8 {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Uint(v) => Ok(u8::try_from(v)?),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for u32 {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Uint(v) => Ok(u32::try_from(v)?),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for SmcVersion {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            // {rev: major, minor, build, reserved, release (u16)}
+            DataValue::Unknown(data) if data.len() == 6 => Ok(SmcVersion {
+                major: data[0],
+                minor: data[1],
+                build: data[2],
+                release: u16::from_be_bytes(data[4..].try_into()?),
+            }),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for String {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        match val {
+            DataValue::Str(v) => Ok(v),
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+impl ValueParser for DataValue {
+    fn parse(val: DataValue) -> InternalResult<Self> {
+        Ok(val)
+    }
+}
+
+/// The inverse of [`ValueParser`], for values that can be written to the SMC.
+trait ValueEncoder {
+    fn encode(&self, data_type: u32, data_size: u32) -> InternalResult<Vec<u8>>;
+}
+
+impl ValueEncoder for Percent {
+    fn encode(&self, data_type: u32, data_size: u32) -> InternalResult<Vec<u8>> {
+        let level = (self.0.clamp(0.0, 100.0) * f32::from(u8::MAX) / 100.0).round() as u8;
+        match &data_type.to_be_bytes() {
+            b"ui8 " | b"{lks" if data_size > 0 => {
+                let mut data = vec![0; data_size as usize];
+                data[0] = level;
+                Ok(data)
+            }
+            _ => Err(InternalError::_DataValueError),
+        }
+    }
+}
+
+struct Check<'a>(&'a str);
+
+impl<'a> ReadAction for Check<'a> {
+    type Out = DataValue;
+
+    fn key(&self) -> CommandKey {
+        let bytes = self.0.as_bytes();
+        let key = u32::from_be_bytes(bytes.try_into().unwrap());
+        CommandKey(key)
+    }
+}
+
+struct ReadKey<T>(CommandKey, PhantomData<T>);
+
+impl<T> ReadKey<T> {
+    fn new(key: CommandKey) -> Self {
+        Self(key, PhantomData)
+    }
+}
+
+impl<T: ValueParser> ReadAction for ReadKey<T> {
+    type Out = T;
+
+    fn key(&self) -> CommandKey {
+        self.0
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct DataType(DataValue, u32);
+
+static NUMBER_OF_KEYS: CommandKey = smc_key(b"#KEY");
+
+const MAX_GPUS: u8 = 8;
+const MAX_DIMMS: u8 = 10;
+const MAX_DRIVES: u8 = 8;
+
+/// A group of catalog ids whose keys are combined into a single value.
+/// Keys that are missing on the current machine are skipped,
+/// if all keys are missing, the value is 0.0
+struct Sensors {
+    ids: &'static [&'static str],
+    reduce: Reduce,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Reduce {
+    First,
+    Max,
+    Mean,
+    Sum,
+}
+
+impl Reduce {
+    fn apply(self, values: &[f32]) -> f32 {
+        if values.is_empty() {
+            return 0.0;
+        }
+        match self {
+            Reduce::First => values[0],
+            Reduce::Max => values.iter().copied().fold(f32::MIN, f32::max),
+            Reduce::Mean => values.iter().sum::<f32>() / values.len() as f32,
+            Reduce::Sum => values.iter().sum(),
+        }
+    }
+}
+
+enum CoreSensors {
+    /// One key per core, the core number (starting at 1) replaces the placeholder
+    Indexed(&'static str),
+    /// A list of keys per core kind, one per core. Keys that are missing are not counted as a core.
+    Listed {
+        performance: &'static str,
+        efficiency: &'static str,
+    },
+}
+
+/// The catalog ids that differ between platforms.
+struct SensorProfile {
+    cpu_proximity: Sensors,
+    cpu_die: Sensors,
+    cpu_graphics: Sensors,
+    cpu_system_agent: Sensors,
+    cpu_cores: CoreSensors,
+    gpu_proximity: Sensors,
+    gpu_die: Sensors,
+    cpu_core_power: Sensors,
+    cpu_dram_power: Sensors,
+    cpu_gfx_power: Sensors,
+    cpu_rail_power: Sensors,
+    cpu_total_power: Sensors,
+    gpu_power: Sensors,
+}
+
+const fn first(ids: &'static [&'static str]) -> Sensors {
+    Sensors {
+        ids,
+        reduce: Reduce::First,
+    }
+}
+
+const fn none() -> Sensors {
+    first(&[])
+}
+
+static INTEL_PROFILE: SensorProfile = SensorProfile {
+    cpu_proximity: first(&["cpu.proximity"]),
+    cpu_die: first(&["cpu.die"]),
+    cpu_graphics: first(&["cpu.graphics"]),
+    cpu_system_agent: first(&["cpu.system_agent"]),
+    cpu_cores: CoreSensors::Indexed("cpu.core"),
+    gpu_proximity: first(&["gpu.proximity"]),
+    gpu_die: first(&["gpu.die"]),
+    cpu_core_power: first(&["power.cpu.core"]),
+    cpu_dram_power: first(&["power.cpu.dram"]),
+    cpu_gfx_power: first(&["power.cpu.gfx"]),
+    cpu_rail_power: first(&["power.cpu.rail"]),
+    cpu_total_power: first(&["power.cpu.total"]),
+    gpu_power: first(&["power.gpu"]),
+};
+
+// Apple Silicon has no proximity or die sensors for the whole CPU.
+// Instead, every core cluster reports a set of temperatures, from which we derive the
+// CPU proximity (mean) and die (max) temperatures.
+// The GPU is part of the SoC, so it is reported as the integrated graphics as well.
+// Which keys belong to which chip generation is decided by the catalog.
+static APPLE_SILICON_CORES: [&str; 2] = ["cpu.core.performance", "cpu.core.efficiency"];
+static APPLE_SILICON_GPU: [&str; 1] = ["gpu.cluster"];
+
+static APPLE_SILICON_PROFILE: SensorProfile = SensorProfile {
+    cpu_proximity: Sensors {
+        ids: &APPLE_SILICON_CORES,
+        reduce: Reduce::Mean,
+    },
+    cpu_die: Sensors {
+        ids: &APPLE_SILICON_CORES,
+        reduce: Reduce::Max,
+    },
+    cpu_graphics: Sensors {
+        ids: &APPLE_SILICON_GPU,
+        reduce: Reduce::Max,
+    },
+    cpu_system_agent: none(),
+    cpu_cores: CoreSensors::Listed {
+        performance: "cpu.core.performance",
+        efficiency: "cpu.core.efficiency",
+    },
+    gpu_proximity: Sensors {
+        ids: &APPLE_SILICON_GPU,
+        reduce: Reduce::Mean,
+    },
+    gpu_die: Sensors {
+        ids: &APPLE_SILICON_GPU,
+        reduce: Reduce::Max,
+    },
+    cpu_core_power: first(&["power.cpu.core"]),
+    cpu_dram_power: first(&["power.cpu.dram"]),
+    cpu_gfx_power: first(&["power.cpu.gfx"]),
+    cpu_rail_power: none(),
+    cpu_total_power: Sensors {
+        ids: &["power.cpu.core", "power.cpu.gfx", "power.cpu.dram"],
+        reduce: Reduce::Sum,
+    },
+    gpu_power: first(&["power.gpu"]),
+};
+
+const fn smc_key(key: &'static [u8]) -> CommandKey {
+    let key = [key[0], key[1], key[2], key[3]];
+    let key = u32::from_be_bytes(key);
+    CommandKey(key)
+}
+
+macro_rules! int_tpe {
+    ($data:ident as $narrow:ty as $wide:ty as $out:ident) => {{
+        Ok($crate::DataValue::$out(<$wide>::from(
+            <$narrow>::from_be_bytes($data.try_into()?),
+        )))
+    }};
+    ($data:ident as $wide:ty as $out:ident) => {{
+        Ok($crate::DataValue::$out(<$wide>::from_be_bytes(
+            $data.try_into()?,
+        )))
+    }};
+}
+
+impl DataValue {
+    fn convert(data: &[u8], tpe: u32) -> InternalResult<Self> {
+        let tpe_str = tpe.to_be_bytes();
+
+        match &tpe_str {
+            b"flag" => return Ok(DataValue::Flag(!data.is_empty() && data[0] != 0)),
+            b"flt " => return Ok(DataValue::Float(f32::from_ne_bytes(data.try_into()?))),
+            b"hex_" => match data.len() {
+                1 => return int_tpe!(data as u8 as u64 as Uint),
+                2 => return int_tpe!(data as u16 as u64 as Uint),
+                4 => return int_tpe!(data as u32 as u64 as Uint),
+                8 => return int_tpe!(data as u64 as u64 as Uint),
+                _ => {}
+            },
+            b"ch8*" => {
+                let has_nul_termiantor = data.contains(&0);
+                let s = if has_nul_termiantor {
+                    unsafe { ::std::ffi::CStr::from_ptr(data.as_ptr() as *const _) }
+                        .to_string_lossy()
+                        .into_owned()
+                } else {
+                    let mut data = data.to_vec();
+                    data.push(0);
+                    unsafe { ::std::ffi::CStr::from_ptr(data.as_ptr() as *const _) }
+                        .to_string_lossy()
+                        .into_owned()
+                };
+                return Ok(DataValue::Str(s));
+            }
+            _ => {}
+        }
+
+        match &tpe_str[..2] {
+            b"fp" => {
+                // fpXY, unsigned fixed point floats, X = integer width, Y = floating width
+                let i = char_to_int(tpe_str[2]);
+                let f = char_to_int(tpe_str[3]);
+                if i + f == 16 {
+                    let unsigned = u16::from_be_bytes(data.try_into()?);
+                    return decode_fp_float(f32::from(unsigned), f);
+                }
+            }
+            b"sp" => {
+                // spXY, signed fixed point floats, X = integer width, Y = floating width
+                let i = char_to_int(tpe_str[2]);
+                let f = char_to_int(tpe_str[3]);
+                if i + f == 15 {
+                    let signed = i16::from_be_bytes(data.try_into()?);
+                    return decode_fp_float(f32::from(signed), f);
+                }
+            }
+            b"ui" => match &tpe_str[2..] {
+                b"8 " => return int_tpe!(data as u8 as u64 as Uint),
+                b"16" => return int_tpe!(data as u16 as u64 as Uint),
+                b"32" => return int_tpe!(data as u32 as u64 as Uint),
+                b"64" => return int_tpe!(data as u64 as Uint),
+                _ => {}
+            },
+            b"si" => match &tpe_str[2..] {
+                b"8 " => return int_tpe!(data as i8 as i64 as Int),
+                b"16" => return int_tpe!(data as i16 as i64 as Int),
+                b"32" => return int_tpe!(data as i32 as i64 as Int),
+                b"64" => return int_tpe!(data as i64 as Int),
+                _ => {}
+            },
+            _ => {}
+        }
+
+        Ok(DataValue::Unknown(data.to_vec()))
+    }
+}
+
+fn char_to_int(c: u8) -> u8 {
+    static A: u8 = b'a';
+    static F: u8 = b'f';
+    static N0: u8 = b'0';
+    static N9: u8 = b'9';
+
+    if c >= A && c <= F {
+        c - A + 10
+    } else if c >= N0 && c <= N9 {
+        c - N0
+    } else {
+        0
+    }
+}
+
+#[inline]
+fn decode_fp_float(float: f32, f: u8) -> InternalResult<DataValue> {
+    Ok(DataValue::Float(float / f32::from(1_u16 << f)))
+}
+
+impl Into<u32> for DataType {
+    fn into(self) -> u32 {
+        self.1
+    }
+}
+
+#[derive(Debug)]
+struct KeyInfo {
+    key: u32,
+    data_type: u32,
+    data_size: u32,
+}
+
+impl StdError for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SmcNotAvailable => write!(f, "SMC is not available, are you running on a Mac?"),
+            Error::InsufficientPrivileges => {
+                write!(f, "Could not perform SMC operation, try running with sudo")
+            }
+            Error::SmcError(code) => write!(f, "Could not perform SMC operation: {:08x}", code),
+            Error::DataError { key, tpe } => write!(
+                f,
+                "Could not read data for key {} of type {}",
+                tpe_name(key),
+                tpe_name(tpe)
+            ),
+            Error::KeyNotAvailable(key) => write!(f, "{} is not available on this machine", key),
+        }
+    }
+}
+
+fn tpe_name(tpe: &u32) -> String {
+    let bytes = tpe.to_be_bytes();
+    String::from_utf8_lossy(&bytes).to_string()
+}
+
+type InternalResult<T> = std::result::Result<T, InternalError>;
+enum InternalError {
+    SmcNotFound,
+    SmcFailedToOpen(i32),
+    NotPrivlileged,
+    UnknownSmc(i32, u8),
+    _UnknownKey,
+    _DataKeyError(u32),
+    _DataValueError,
+    // for pub error
+    DataError { key: u32, tpe: u32 },
+    KeyNotAvailable(String),
+}
+
+impl From<TryFromSliceError> for InternalError {
+    fn from(_: TryFromSliceError) -> Self {
+        Self::_DataValueError
+    }
+}
+
+impl From<TryFromIntError> for InternalError {
+    fn from(_: TryFromIntError) -> Self {
+        Self::_DataValueError
+    }
+}
+
+impl From<InternalError> for Error {
+    fn from(ie: InternalError) -> Self {
+        match ie {
+            InternalError::SmcNotFound => Error::SmcNotAvailable,
+            InternalError::SmcFailedToOpen(_) => Error::SmcNotAvailable,
+            InternalError::NotPrivlileged => Error::InsufficientPrivileges,
+            InternalError::UnknownSmc(code, _) => Error::SmcError(code),
+            InternalError::DataError { key, tpe } => Error::DataError { key, tpe },
+            InternalError::KeyNotAvailable(key) => Error::KeyNotAvailable(key),
+            InternalError::_UnknownKey => unreachable!(),
+            InternalError::_DataValueError => unreachable!(),
+            InternalError::_DataKeyError(_) => unreachable!(),
+        }
+    }
+}
+
+/// The data of a single key as it was read from the SMC, before it is decoded.
+#[derive(Clone, Debug, PartialEq)]
+struct RawValue {
+    data_type: u32,
+    data: Vec<u8>,
+}
+
+/// Where the data is read from.
+/// Everything except for the actual SMC is only used to run tests without hardware.
+#[derive(Debug)]
+enum Backend {
+    Smc(cffi::SMCConnection),
+    #[cfg(test)]
+    Fixture(fixture::Fixture),
+}
+
+impl Backend {
+    fn read_value<R>(&mut self, op: R) -> InternalResult<R::Out>
+    where
+        R: ReadAction,
+        R::Out: Default,
+    {
+        Ok(self.opt_read_value(op)?.unwrap_or_default())
+    }
+
+    fn opt_read_value<R: ReadAction>(&mut self, op: R) -> InternalResult<Option<R::Out>> {
+        let result = self.try_read_value(op);
+        match result {
+            Ok(result) => Ok(Some(result)),
+            Err(InternalError::_UnknownKey) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn try_read_value<R: ReadAction>(&mut self, op: R) -> InternalResult<R::Out> {
+        let key = *op.key();
+        let result = self.read_key(key).map_err(|e| match e {
+            InternalError::_DataKeyError(tpe) => InternalError::DataError { key, tpe },
+            otherwise => otherwise,
+        })?;
+        let tpe = result.data_type;
+        let data_error = |e| match e {
+            InternalError::_DataValueError => InternalError::DataError { key, tpe },
+            otherwise => otherwise,
+        };
+        // the size of the data does not always match its type, e.g. a `flt ` with 2 bytes
+        let data = DataValue::convert(&result.data, tpe).map_err(data_error)?;
+        op.parse(data).map_err(data_error)
+    }
+
+    /// Like [`Backend::opt_read_value`], but also returns `None`
+    /// if the value cannot be decoded into the requested type.
+    fn read_sensor<T: ValueParser>(&mut self, key: CommandKey) -> InternalResult<Option<T>> {
+        match self.try_read_value(ReadKey::new(key)) {
+            Ok(value) => Ok(Some(value)),
+            Err(InternalError::DataError { .. }) | Err(InternalError::_UnknownKey) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn write_value<R>(&mut self, op: R, value: R::Out) -> InternalResult<()>
+    where
+        R: ReadAction,
+        R::Out: ValueEncoder,
+    {
+        let KeyInfo {
+            key,
+            data_type,
+            data_size,
+        } = self.key_info(op)?;
+        let data = value.encode(data_type, data_size).map_err(|e| match e {
+            InternalError::_DataValueError => InternalError::DataError {
+                key,
+                tpe: data_type,
+            },
+            otherwise => otherwise,
+        })?;
+        let result = match self {
+            Backend::Smc(conn) => conn.write_key(key, &data),
+            #[cfg(test)]
+            Backend::Fixture(fixture) => fixture.write_key(key, &data),
+        };
+        result.map_err(|e| match e {
+            InternalError::_UnknownKey | InternalError::_DataKeyError(_) => {
+                InternalError::DataError {
+                    key,
+                    tpe: data_type,
+                }
+            }
+            otherwise => otherwise,
+        })
+    }
+
+    fn key_info<O: ReadAction>(&mut self, op: O) -> InternalResult<KeyInfo> {
+        let key = *op.key();
+        let result = match self {
+            Backend::Smc(conn) => conn.key_info(key),
+            #[cfg(test)]
+            Backend::Fixture(fixture) => fixture.key_info(key),
+        };
+        result.map_err(|e| match e {
+            InternalError::_UnknownKey => InternalError::KeyNotAvailable(tpe_name(&key)),
+            otherwise => otherwise,
+        })
+    }
+
+    fn key_info_by_index(&mut self, index: u32) -> InternalResult<KeyInfo> {
+        let result = match self {
+            Backend::Smc(conn) => conn.key_info_by_index(index),
+            #[cfg(test)]
+            Backend::Fixture(fixture) => fixture.key_info_by_index(index),
+        };
+        result.map_err(|e| match e {
+            InternalError::_UnknownKey => {
+                InternalError::KeyNotAvailable(format!("The key at index {}", index))
+            }
+            otherwise => otherwise,
+        })
+    }
+
+    fn read_key(&mut self, key: u32) -> InternalResult<RawValue> {
+        match self {
+            Backend::Smc(conn) => conn.read_key(key),
+            #[cfg(test)]
+            Backend::Fixture(fixture) => fixture.read_key(key),
+        }
+    }
+
+    fn version(&mut self) -> InternalResult<SmcVersion> {
+        let result = match self {
+            Backend::Smc(conn) => conn.version(),
+            #[cfg(test)]
+            Backend::Fixture(_) => Err(InternalError::_UnknownKey),
+        };
+        match result {
+            Err(InternalError::_UnknownKey) => Ok(SmcVersion::default()),
+            otherwise => otherwise,
+        }
+    }
+
+    fn power_limits(&mut self) -> InternalResult<PowerLimits> {
+        let result = match self {
+            Backend::Smc(conn) => conn.power_limits(),
+            #[cfg(test)]
+            Backend::Fixture(fixture) => fixture.power_limits(),
+        };
+        match result {
+            Err(InternalError::_UnknownKey) => Ok(PowerLimits::default()),
+            otherwise => otherwise,
+        }
+    }
+}
+
+mod cffi {
+    use super::*;
+    #[cfg(target_os = "macos")]
+    use std::{ffi::CStr, ptr};
+    use std::{
+        mem::{size_of, size_of_val},
+        os::raw::c_void,
+    };
+
+    #[allow(non_camel_case_types)]
+    type kern_return_t = i32;
+    #[allow(non_camel_case_types)]
+    type ipc_port_t = *mut c_void;
+    #[allow(non_camel_case_types)]
+    type mach_port_t = ipc_port_t;
+    #[allow(non_camel_case_types)]
+    type io_object_t = mach_port_t;
+    #[allow(non_camel_case_types)]
+    type io_connect_t = io_object_t;
+    #[allow(non_camel_case_types)]
+    type task_t = *mut c_void;
+    #[allow(non_camel_case_types)]
+    type task_port_t = task_t;
+    #[allow(non_camel_case_types)]
+    type io_service_t = io_object_t;
+
+    const MACH_PORT_NULL: mach_port_t = 0 as mach_port_t;
+    const MASTER_PORT_DEFAULT: mach_port_t = MACH_PORT_NULL;
+
+    const KERN_SUCCESS: kern_return_t = 0;
+    const RETURN_SUCCESS: kern_return_t = KERN_SUCCESS;
+
+    const SYS_IOKIT: kern_return_t = (0x38 & 0x3f) << 26;
+    const SUB_IOKIT_COMMON: kern_return_t = 0;
+    const RETURN_NOT_PRIVILEGED: kern_return_t = SYS_IOKIT | SUB_IOKIT_COMMON | 0x2c1;
+
+    const KERNEL_INDEX_SMC: u32 = 2;
+
+    #[cfg(target_os = "macos")]
+    pub(super) fn num_cpus() -> i32 {
+        let mut cpus: i32 = 0;
+        let mut cpus_size = std::mem::size_of_val(&cpus);
+
+        let sysctl_name =
+            CStr::from_bytes_with_nul(b"hw.physicalcpu\0").expect("byte literal is missing NUL");
+
+        unsafe {
+            if 0 != libc::sysctlbyname(
+                sysctl_name.as_ptr(),
+                &mut cpus as *mut _ as *mut _,
+                &mut cpus_size as *mut _ as *mut _,
+                ptr::null_mut(),
+                0,
+            ) {
+                // On ARM targets, processors could be turned off to save power.
+                // Use `_SC_NPROCESSORS_CONF` to get the real number.
+                #[cfg(any(target_arch = "arm", target_arch = "aarch64"))]
+                const CONF_NAME: libc::c_int = libc::_SC_NPROCESSORS_CONF;
+                #[cfg(not(any(target_arch = "arm", target_arch = "aarch64")))]
+                const CONF_NAME: libc::c_int = libc::_SC_NPROCESSORS_ONLN;
+
+                cpus = libc::sysconf(CONF_NAME) as i32;
+            }
+        }
+
+        cpus.max(1)
+    }
+
+    #[cfg(target_os = "macos")]
+    fn sysctl_i32(name: &[u8]) -> Option<i32> {
+        let mut value: i32 = 0;
+        let mut value_size = size_of_val(&value);
+
+        let sysctl_name = CStr::from_bytes_with_nul(name).expect("byte literal is missing NUL");
+
+        let result = unsafe {
+            libc::sysctlbyname(
+                sysctl_name.as_ptr(),
+                &mut value as *mut _ as *mut _,
+                &mut value_size as *mut _ as *mut _,
+                ptr::null_mut(),
+                0,
+            )
+        };
+
+        if result == 0 {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the number of physical performance and efficiency cores.
+    /// Returns `None` if the OS does not report performance levels (before macOS 12).
+    #[cfg(target_os = "macos")]
+    pub(super) fn perf_levels() -> Option<[u8; 2]> {
+        let levels = sysctl_i32(b"hw.nperflevels\0")?;
+        let performance = sysctl_i32(b"hw.perflevel0.physicalcpu\0")?;
+        let efficiency = if levels > 1 {
+            sysctl_i32(b"hw.perflevel1.physicalcpu\0")?
+        } else {
+            0
+        };
+        let cpus = |cpus: i32| u8::try_from(cpus.max(0)).unwrap_or(u8::MAX);
+        Some([cpus(performance), cpus(efficiency)])
+    }
+
+    #[cfg(target_os = "macos")]
+    pub(super) fn platform() -> Platform {
+        // `hw.optional.arm64` is also set when running under Rosetta, `target_arch` is not.
+        if sysctl_i32(b"hw.optional.arm64\0") != Some(1) {
+            return Platform::Intel;
+        }
+
+        let mut brand = [0_u8; 128];
+        let brand = sysctl_bytes(b"machdep.cpu.brand_string\0", &mut brand);
+        Platform::AppleSilicon(apple_silicon_generation(brand))
+    }
+
+    /// Returns the model identifier, e.g. `MacBookPro15,1`, or an empty string.
+    #[cfg(target_os = "macos")]
+    pub(super) fn model() -> String {
+        let mut model = [0_u8; 64];
+        let model = sysctl_bytes(b"hw.model\0", &mut model);
+        let model = model.split(|&c| c == 0).next().unwrap_or_default();
+        String::from_utf8_lossy(model).into_owned()
+    }
+
+    /// Reads a string value into the buffer and returns the part that was written.
+    #[cfg(target_os = "macos")]
+    fn sysctl_bytes<'a>(name: &[u8], buf: &'a mut [u8]) -> &'a [u8] {
+        let mut size = buf.len();
+
+        let sysctl_name = CStr::from_bytes_with_nul(name).expect("byte literal is missing NUL");
+
+        if 0 != unsafe {
+            libc::sysctlbyname(
+                sysctl_name.as_ptr(),
+                buf.as_mut_ptr() as *mut _,
+                &mut size as *mut _ as *mut _,
+                ptr::null_mut(),
+                0,
+            )
+        } {
+            size = 0;
+        }
+
+        &buf[..size.min(buf.len())]
+    }
+
+    /// Parses the generation from a brand string like `Apple M2 Pro`
+    #[cfg(target_os = "macos")]
+    pub(super) fn apple_silicon_generation(brand: &[u8]) -> u8 {
+        let brand = String::from_utf8_lossy(brand);
+        brand
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix('M')?.parse().ok())
+            .unwrap_or(0)
+    }
+
+    #[derive(Debug)]
+    pub(super) struct SMCConnection {
+        conn: io_connect_t,
+    }
+
+    // SAFETY: The connection is a mach port name, which is valid in the whole task
+    // and not tied to the thread that opened it. It is only used through `&mut self`,
+    // so it is never used by two threads at the same time.
+    unsafe impl Send for SMCConnection {}
+
+    impl Drop for SMCConnection {
+        fn drop(&mut self) {
+            unsafe { _smc_close(self.conn) }
+        }
+    }
+
+    impl SMCConnection {
+        pub(super) fn new() -> InternalResult<Self> {
+            let conn = unsafe { _smc_open() }?;
+            Ok(Self { conn })
+        }
+
+        pub(super) fn read_key(&mut self, key: u32) -> InternalResult<RawValue> {
+            let result = unsafe { _smc_read_key(self.conn, key) }?;
+            Ok(RawValue {
+                data_type: result.data_type,
+                data: result.bytes.0[..result.data_size as usize].to_vec(),
+            })
+        }
+
+        pub(super) fn write_key(&mut self, key: u32, data: &[u8]) -> InternalResult<()> {
+            unsafe { _smc_write_key(self.conn, key, data) }
+        }
+
+        pub(super) fn key_info(&mut self, key: u32) -> InternalResult<KeyInfo> {
+            unsafe { _smc_key_info(self.conn, key) }
+        }
+
+        pub(super) fn key_info_by_index(&mut self, index: u32) -> InternalResult<KeyInfo> {
+            unsafe { _smc_key_index_info(self.conn, index) }
+        }
+
+        pub(super) fn version(&mut self) -> InternalResult<SmcVersion> {
+            let version = unsafe { _smc_version(self.conn) }?;
+            Ok(SmcVersion {
+                major: version.major,
+                minor: version.minor,
+                build: version.build,
+                release: version.release,
+            })
+        }
+
+        pub(super) fn power_limits(&mut self) -> InternalResult<PowerLimits> {
+            let limits = unsafe { _smc_power_limits(self.conn) }?;
+            Ok(PowerLimits {
+                version: limits.version,
+                cpu: limits.cpu_p_limit,
+                gpu: limits.gpu_p_limit,
+                memory: limits.mem_p_limit,
+            })
+        }
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    #[repr(u8)]
+    enum SMCReadCommand {
+        Data = 5,
+        WriteData = 6,
+        ByIndex = 8,
+        KeyInfo = 9,
+        PowerLimit = 11,
+        Version = 12,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    struct SMCKeyData {
+        key: u32,
+        version: SMCKeyDataVersion,
+        p_limit_data: SMCKeyDataLimitData,
+        key_info: SMCKeyDataKeyInfo,
+        result: u8,
+        status: u8,
+        data8: u8,
+        data32: u32,
+        bytes: SMCBytes,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    struct SMCKeyDataVersion {
+        major: u8,
+        minor: u8,
+        build: u8,
+        reserved: u8,
+        release: u16,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    struct SMCKeyDataLimitData {
+        version: u16,
+        length: u16,
+        cpu_p_limit: u32,
+        gpu_p_limit: u32,
+        mem_p_limit: u32,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    struct SMCKeyDataKeyInfo {
+        data_size: u32,
+        data_type: u32,
+        data_attributes: u8,
+    }
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(transparent)]
+    struct SMCBytes([u8; 32]);
+
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    struct SMCVal {
+        key: u32,
+        data_size: u32,
+        data_type: u32,
+        bytes: SMCBytes,
+    }
+
+    #[repr(C)]
+    struct __CFDictionary(c_void);
+
+    type CFDictionaryRef = *const __CFDictionary;
+    type CFMutableDictionaryRef = *mut __CFDictionary;
+
+    #[link(name = "IOKit", kind = "framework")]
+    extern "C" {
+        fn IOServiceMatching(name: *const u8) -> CFMutableDictionaryRef;
+
+        fn IOServiceGetMatchingService(
+            master_port: mach_port_t,
+            matching: CFDictionaryRef,
+        ) -> io_service_t;
+
+        fn IOServiceOpen(
+            service: io_service_t,
+            owning_task: task_port_t,
+            r#type: u32,
+            connect: *const io_connect_t,
+        ) -> kern_return_t;
+
+        fn IOServiceClose(connect: io_connect_t) -> kern_return_t;
+
+        fn IOConnectCallStructMethod(
+            connection: mach_port_t,
+            selector: u32,
+            input: *const c_void,
+            input_size: usize,
+            output: *mut c_void,
+            output_size: *mut usize,
+        ) -> kern_return_t;
+
+        fn IOObjectRelease(object: io_object_t) -> kern_return_t;
+
+        fn mach_task_self() -> mach_port_t;
+    }
+
+    unsafe fn _smc_open() -> InternalResult<io_connect_t> {
+        let matching_dictionary = IOServiceMatching(b"AppleSMC\0".as_ptr());
+        let device = IOServiceGetMatchingService(MASTER_PORT_DEFAULT, matching_dictionary);
+
+        if device.is_null() {
+            return Err(InternalError::SmcNotFound);
+        }
+
+        let result: kern_return_t;
+        let conn: io_connect_t = MASTER_PORT_DEFAULT;
+        result = IOServiceOpen(device, mach_task_self(), 0, &conn);
+        let _ = IOObjectRelease(device);
+
+        if result != RETURN_SUCCESS {
+            return Err(InternalError::SmcFailedToOpen(result));
+        }
+
+        Ok(conn)
+    }
+
+    unsafe fn _smc_close(conn: io_connect_t) {
+        let _ = IOServiceClose(conn);
+    }
+
+    unsafe fn _smc_read_key(conn: mach_port_t, key: u32) -> InternalResult<SMCVal> {
+        let mut input = SMCKeyData::default();
+        input.key = key;
+        input.data8 = SMCReadCommand::KeyInfo as u8;
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        let data_type = output.key_info.data_type;
+        let data_size = output.key_info.data_size;
+
+        if data_size > 32 {
+            return Err(InternalError::_DataKeyError(data_type));
+        }
+
+        input.key_info.data_size = data_size;
+        input.data8 = SMCReadCommand::Data as u8;
+
+        _smc_call(conn, &input, &mut output)?;
+
+        let val = SMCVal {
+            key,
+            data_size,
+            data_type,
+            bytes: output.bytes,
+        };
+
+        Ok(val)
+    }
+
+    unsafe fn _smc_write_key(conn: mach_port_t, key: u32, data: &[u8]) -> InternalResult<()> {
+        let mut input = SMCKeyData {
+            key,
+            data8: SMCReadCommand::KeyInfo as u8,
+            ..SMCKeyData::default()
+        };
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        let data_type = output.key_info.data_type;
+        let data_size = output.key_info.data_size;
+
+        if data_size as usize != data.len() || data.len() > 32 {
+            return Err(InternalError::_DataKeyError(data_type));
+        }
+
+        input.key_info.data_size = data_size;
+        input.data8 = SMCReadCommand::WriteData as u8;
+        input.bytes.0[..data.len()].copy_from_slice(data);
+
+        _smc_call(conn, &input, &mut output)
+    }
+
+    unsafe fn _smc_key_info(conn: mach_port_t, key: u32) -> InternalResult<KeyInfo> {
+        let mut input = SMCKeyData::default();
+        input.key = key;
+        input.data8 = SMCReadCommand::KeyInfo as u8;
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        let data_type = output.key_info.data_type;
+        let data_size = output.key_info.data_size;
+
+        Ok(KeyInfo {
+            key,
+            data_type,
+            data_size,
+        })
+    }
+
+    unsafe fn _smc_key_index_info(conn: mach_port_t, index: u32) -> InternalResult<KeyInfo> {
+        let mut input = SMCKeyData::default();
+        input.data8 = SMCReadCommand::ByIndex as u8;
+        input.data32 = index;
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        let key = output.key;
+        let data_type = output.key_info.data_type;
+        let data_size = output.key_info.data_size;
+
+        Ok(KeyInfo {
+            key,
+            data_type,
+            data_size,
+        })
+    }
+
+    unsafe fn _smc_version(conn: mach_port_t) -> InternalResult<SMCKeyDataVersion> {
+        let input = SMCKeyData {
+            data8: SMCReadCommand::Version as u8,
+            ..SMCKeyData::default()
+        };
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        Ok(output.version)
+    }
+
+    unsafe fn _smc_power_limits(conn: mach_port_t) -> InternalResult<SMCKeyDataLimitData> {
+        let input = SMCKeyData {
+            data8: SMCReadCommand::PowerLimit as u8,
+            ..SMCKeyData::default()
+        };
+
+        let mut output = SMCKeyData::default();
+        _smc_call(conn, &input, &mut output)?;
+
+        Ok(output.p_limit_data)
+    }
+
+    unsafe fn _smc_call(
+        conn: mach_port_t,
+        input: &SMCKeyData,
+        output: &mut SMCKeyData,
+    ) -> InternalResult<()> {
+        let mut output_size = size_of::<SMCKeyData>();
+
+        let result = IOConnectCallStructMethod(
+            conn,
+            KERNEL_INDEX_SMC,
+            input as *const _ as *const c_void,
+            size_of::<SMCKeyData>(),
+            output as *mut _ as *mut c_void,
+            &mut output_size,
+        );
+
+        if result == RETURN_NOT_PRIVILEGED {
+            return Err(InternalError::NotPrivlileged);
+        }
+        if result != RETURN_SUCCESS {
+            return Err(InternalError::UnknownSmc(result, output.result));
+        }
+        if output.result == 132 {
+            return Err(InternalError::_UnknownKey);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod fixture {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    /// A backend that serves a key dump in the format of `smc -l`, e.g.
+    /// `  TC0P  [sp78]  52.25 (bytes 34 40)`
+    /// Power limits are not part of the dump and can be set by tests.
+    #[derive(Debug, Default)]
+    pub(super) struct Fixture {
+        keys: BTreeMap<u32, RawValue>,
+        pub(super) power_limits: Option<PowerLimits>,
+    }
+
+    impl Fixture {
+        pub(super) fn parse(dump: &str) -> Self {
+            let mut keys = BTreeMap::new();
+            for line in dump.lines() {
+                let line = line.trim_start_matches(' ');
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                let bytes = line.as_bytes();
+                let key = u32::from_be_bytes(bytes[..4].try_into().unwrap());
+                let data_type = u32::from_be_bytes(bytes[7..11].try_into().unwrap());
+                let bytes = line
+                    .rsplit("(bytes ")
+                    .next()
+                    .and_then(|bytes| bytes.strip_suffix(')'))
+                    .unwrap_or_else(|| panic!("missing bytes in fixture line: {}", line));
+                let data = bytes
+                    .split_whitespace()
+                    .map(|byte| u8::from_str_radix(byte, 16).expect("invalid byte in fixture"))
+                    .collect();
+                let _ = keys.insert(key, RawValue { data_type, data });
+            }
+            let number_of_keys = keys.len() as u32 + 1;
+            let _ = keys.insert(
+                *NUMBER_OF_KEYS,
+                RawValue {
+                    data_type: *smc_key(b"ui32"),
+                    data: number_of_keys.to_be_bytes().to_vec(),
+                },
+            );
+            Self {
+                keys,
+                power_limits: None,
+            }
+        }
+
+        pub(super) fn read_key(&mut self, key: u32) -> InternalResult<RawValue> {
+            self.keys
+                .get(&key)
+                .cloned()
+                .ok_or(InternalError::_UnknownKey)
+        }
+
+        pub(super) fn write_key(&mut self, key: u32, data: &[u8]) -> InternalResult<()> {
+            let value = self.keys.get_mut(&key).ok_or(InternalError::_UnknownKey)?;
+            if value.data.len() != data.len() {
+                return Err(InternalError::_DataKeyError(value.data_type));
+            }
+            value.data = data.to_vec();
+            Ok(())
+        }
+
+        pub(super) fn key_info(&mut self, key: u32) -> InternalResult<KeyInfo> {
+            let value = self.keys.get(&key).ok_or(InternalError::_UnknownKey)?;
+            Ok(KeyInfo {
+                key,
+                data_type: value.data_type,
+                data_size: value.data.len() as u32,
+            })
+        }
+
+        pub(super) fn key_info_by_index(&mut self, index: u32) -> InternalResult<KeyInfo> {
+            let key = *self
+                .keys
+                .keys()
+                .nth(index as usize)
+                .ok_or(InternalError::_UnknownKey)?;
+            self.key_info(key)
+        }
+
+        pub(super) fn power_limits(&mut self) -> InternalResult<PowerLimits> {
+            self.power_limits.ok_or(InternalError::_UnknownKey)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static APPLE_M1: &str = include_str!("../fixtures/apple_m1.txt");
+    static APPLE_M2: &str = include_str!("../fixtures/apple_m2.txt");
+    static INTEL_MBP15: &str = include_str!("../fixtures/intel_mbp15.txt");
+
+    impl Smc {
+        fn fixture(platform: Platform, dump: &str) -> Self {
+            Smc {
+                inner: Backend::Fixture(fixture::Fixture::parse(dump)),
+                platform,
+                model: String::new(),
+                catalog: Catalog::builtin(),
+                thresholds: Box::new(DefaultThresholds),
+                plausibility: Plausibility::default(),
+                perf_levels: None,
+                cpu_cores: None,
+                system_info: None,
+            }
+        }
+    }
+
+    #[test]
+    fn apple_m1_cpu_temperature() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        let cpu = smc.cpu_temperature().unwrap();
+        assert_eq!(cpu.proximity, Celsius(45.375));
+        assert_eq!(cpu.die, Celsius(48.75));
+        assert_eq!(cpu.graphics, Celsius(36.5));
+        assert_eq!(cpu.system_agent, Celsius(0.0));
+    }
+
+    fn core(kind: CoreKind, index: u8, temperature: f32) -> CoreTemperature {
+        CoreTemperature {
+            kind,
+            index,
+            temperature: Celsius(temperature),
+        }
+    }
+
+    #[test]
+    fn apple_m1_cpu_cores() {
+        use CoreKind::*;
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        smc.perf_levels = Some([8, 2]);
+        let cores = smc
+            .cpu_core_temps()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            cores,
+            [
+                core(Performance, 0, 46.5),
+                core(Performance, 1, 47.25),
+                core(Performance, 2, 45.0),
+                core(Performance, 3, 48.75),
+                core(Performance, 4, 47.5),
+                core(Performance, 5, 46.0),
+                core(Performance, 6, 48.25),
+                core(Performance, 7, 47.0),
+                core(Efficiency, 0, 38.5),
+                core(Efficiency, 1, 39.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn apple_m1_cpu_cores_are_limited_by_perf_levels() {
+        use CoreKind::*;
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        smc.perf_levels = Some([3, 1]);
+        let cores = smc
+            .cpu_core_temps()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            cores,
+            [
+                core(Performance, 0, 46.5),
+                core(Performance, 1, 47.25),
+                core(Performance, 2, 45.0),
+                core(Efficiency, 0, 38.5),
+            ]
+        );
+    }
+
+    #[test]
+    fn apple_m1_cpu_cores_in_reverse() {
+        use CoreKind::*;
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        smc.perf_levels = Some([8, 2]);
+        let cores = smc
+            .cpu_core_temps()
+            .unwrap()
+            .rev()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(cores.len(), 10);
+        assert_eq!(cores[0], core(Efficiency, 1, 39.0));
+        assert_eq!(cores[9], core(Performance, 0, 46.5));
+
+        let mut cores = smc.cpu_core_temps().unwrap();
+        assert_eq!(
+            cores.nth_back(1).unwrap().unwrap(),
+            core(Efficiency, 0, 38.5)
+        );
+        assert_eq!(cores.next().unwrap().unwrap(), core(Performance, 0, 46.5));
+        assert_eq!(cores.count(), 7);
+        assert!(matches!(
+            smc.cpu_core_temperature(10),
+            Err(Error::KeyNotAvailable(_))
+        ));
+    }
+
+    #[test]
+    fn apple_m1_gpu() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        let gpu = smc.gpu_temperature().unwrap();
+        assert_eq!(gpu.proximity, Celsius(35.75));
+        assert_eq!(gpu.die, Celsius(36.5));
+
+        let gpus = smc.gpus().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            gpus,
+            [GpuDetail {
+                proximity: Celsius(35.75),
+                die: Celsius(36.5),
+                power: Watt(0.5),
+            }]
+        );
+        let reversed = smc.gpus().unwrap().rev().collect::<Result<Vec<_>>>();
+        assert_eq!(reversed.unwrap(), gpus);
+    }
+
+    #[test]
+    fn apple_m1_power() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        let power = smc.cpu_power().unwrap();
+        assert_eq!(
+            power,
+            CpuPower {
+                core: Watt(3.25),
+                dram: Watt(0.75),
+                gfx: Watt(0.5),
+                rail: Watt(0.0),
+                total: Watt(4.5),
+            }
+        );
+        assert_eq!(smc.gpu_power().unwrap(), Watt(0.5));
+        // the GPU is part of the SoC and must not be counted twice
+        assert!(smc.gpu_is_in_package());
+        assert_eq!(smc.package_with_gpu_power().unwrap(), Some(Watt(4.5)));
+        assert_eq!(smc.power_system_total().unwrap(), Watt(6.5));
+    }
+
+    #[test]
+    fn apple_m2_cpu_cores() {
+        // `Tp09` is an efficiency core on the M1, but a performance core on the M2
+        use CoreKind::*;
+        let mut smc = Smc::fixture(Platform::AppleSilicon(2), APPLE_M2);
+        let cores = smc
+            .cpu_core_temps()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            cores,
+            [
+                core(Performance, 0, 44.0),
+                core(Performance, 1, 45.0),
+                core(Performance, 2, 46.0),
+                core(Performance, 3, 47.0),
+                core(Efficiency, 0, 36.0),
+                core(Efficiency, 1, 39.0),
+                core(Efficiency, 2, 38.0),
+                core(Efficiency, 3, 37.0),
+            ]
+        );
+        let cpu = smc.cpu_temperature().unwrap();
+        assert_eq!(cpu.proximity, Celsius(41.5));
+        assert_eq!(cpu.die, Celsius(47.0));
+    }
+
+    #[test]
+    fn unknown_apple_silicon_uses_a_profile() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(0), APPLE_M1);
+        assert_eq!(smc.cpu_temperature().unwrap().die, Celsius(48.75));
+    }
+
+    #[test]
+    fn intel_keys_on_apple_silicon_are_missing() {
+        let mut smc = Smc::fixture(Platform::Intel, APPLE_M1);
+        assert_eq!(smc.cpu_temperature().unwrap(), CpuTemperatures::default());
+        assert_eq!(smc.gpus().unwrap().count(), 0);
+    }
+
+    #[test]
+    fn intel_cpu_temperature() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let cpu = smc.cpu_temperature().unwrap();
+        assert_eq!(
+            cpu,
+            CpuTemperatures {
+                proximity: Celsius(52.25),
+                die: Celsius(61.5),
+                graphics: Celsius(50.0),
+                system_agent: Celsius(48.5),
+            }
+        );
+    }
+
+    #[test]
+    fn intel_cpu_cores() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        smc.perf_levels = Some([4, 0]);
+        let cores = smc
+            .cpu_core_temps()
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            cores,
+            [
+                core(CoreKind::Performance, 0, 55.0),
+                core(CoreKind::Performance, 1, 57.5),
+                core(CoreKind::Performance, 2, 56.25),
+                core(CoreKind::Performance, 3, 58.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn intel_gpus() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let gpus = smc.gpus().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(
+            gpus,
+            [
+                GpuDetail {
+                    proximity: Celsius(47.0),
+                    die: Celsius(49.75),
+                    power: Watt(4.5),
+                },
+                GpuDetail {
+                    proximity: Celsius(51.0),
+                    die: Celsius(58.0),
+                    power: Watt(9.0),
+                },
+            ]
+        );
+
+        let mut reversed = smc
+            .gpus()
+            .unwrap()
+            .rev()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        reversed.reverse();
+        assert_eq!(reversed, gpus);
+        assert_eq!(smc.gpus().unwrap().nth_back(0).unwrap().unwrap(), gpus[1]);
+    }
+
+    #[test]
+    fn intel_power() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let power = smc.cpu_power().unwrap();
+        assert_eq!(
+            power,
+            CpuPower {
+                core: Watt(8.25),
+                dram: Watt(1.5),
+                gfx: Watt(0.75),
+                rail: Watt(12.5),
+                total: Watt(11.0),
+            }
+        );
+        assert_eq!(smc.gpu_power().unwrap(), Watt(4.5));
+        assert!(!smc.gpu_is_in_package());
+        assert_eq!(smc.package_with_gpu_power().unwrap(), Some(Watt(15.5)));
+    }
+
+    #[test]
+    fn intel_voltages_and_currents() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(
+            smc.voltages().unwrap(),
+            Voltages {
+                cpu_core: Volt(0.875),
+                platform_controller_hub: Volt(1.0),
+                dc_in: Volt(20.25),
+                rail_12v: Volt(12.25),
+                ..Voltages::default()
+            }
+        );
+        assert_eq!(
+            smc.currents().unwrap(),
+            Currents {
+                cpu_core: Ampere(6.5),
+                cpu_rail: Ampere(1.25),
+                dc_in: Ampere(2.75),
+                charger: Ampere(0.5),
+                ..Currents::default()
+            }
+        );
+    }
+
+    #[test]
+    fn malformed_keys_are_data_errors() {
+        // a sp78 with a single byte and a flt with only two bytes
+        let dump = "  TC0P  [sp78]  (bytes 34)\n  PSTR  [flt ]  (bytes 00 00)\n  TC0F  [sp78]  61.5 (bytes 3d 80)\n";
+        let mut smc = Smc::fixture(Platform::Intel, dump);
+        assert!(matches!(
+            smc.cpu_temperature(),
+            Err(Error::DataError { .. })
+        ));
+
+        let sensors = smc.sensors().unwrap();
+        let keys = sensors.iter().map(|s| s.key.as_str()).collect::<Vec<_>>();
+        assert_eq!(keys, ["TC0F"]);
+
+        let discovered = smc.discover().unwrap();
+        let keys = discovered
+            .iter()
+            .map(|s| s.key.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["TC0F"]);
+    }
+
+    #[test]
+    fn intel_fans() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let fans = smc.fans().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        assert_eq!(fans.len(), 2);
+        assert_eq!(fans[0].actual, Rpm(2160.0));
+        assert_eq!(fans[1].max, Rpm(5489.0));
+    }
+
+    #[test]
+    fn fan_health() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let fans = smc.fans().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let mut diagnostics = FanDiagnostics::default();
+        assert_eq!(diagnostics.update(&fans), vec![FanHealth::Ok; 2]);
+        assert!(!diagnostics.is_pending());
+
+        let fan = |actual, target| FanSpeed {
+            actual: Rpm(actual),
+            min: Rpm(1200.0),
+            max: Rpm(6000.0),
+            target: Rpm(target),
+            ..FanSpeed::default()
+        };
+        assert_eq!(fan(0.0, 2000.0).health(), FanHealth::Stalled);
+        assert_eq!(fan(0.0, 0.0).health(), FanHealth::Ok);
+        assert_eq!(fan(0.0, 1200.0).health(), FanHealth::BelowMinimum);
+        assert_eq!(fan(800.0, 1200.0).health(), FanHealth::BelowMinimum);
+        assert_eq!(fan(6500.0, 6000.0).health(), FanHealth::AboveMaximum);
+        assert_eq!(fan(6100.0, 6000.0).health(), FanHealth::Ok);
+        assert!(fan(1950.0, 2000.0).reaches_target());
+        assert!(!fan(1500.0, 2000.0).reaches_target());
+    }
+
+    #[test]
+    fn fan_diagnostics_need_consecutive_misses() {
+        let fan = |actual| FanSpeed {
+            actual: Rpm(actual),
+            min: Rpm(1200.0),
+            max: Rpm(6000.0),
+            target: Rpm(4000.0),
+            ..FanSpeed::default()
+        };
+        let mut diagnostics = FanDiagnostics::new(2);
+        let ok = fan(4000.0);
+        let slow = fan(2500.0);
+        assert_eq!(diagnostics.update(&[ok, slow]), [FanHealth::Ok; 2]);
+        assert!(diagnostics.is_pending());
+        assert_eq!(diagnostics.update(&[slow, ok]), [FanHealth::Ok; 2]);
+        assert_eq!(
+            diagnostics.update(&[slow, slow]),
+            [FanHealth::MissesTarget, FanHealth::Ok]
+        );
+        assert_eq!(
+            diagnostics.update(&[fan(0.0), slow]),
+            [FanHealth::Stalled, FanHealth::MissesTarget]
+        );
+    }
+
+    #[test]
+    fn fan_diagnostics_wait_for_fans_to_spin_up() {
+        let fan = |actual| FanSpeed {
+            actual: Rpm(actual),
+            min: Rpm(1200.0),
+            max: Rpm(6000.0),
+            target: Rpm(2000.0),
+            ..FanSpeed::default()
+        };
+        let mut diagnostics = FanDiagnostics::new(3);
+        assert_eq!(diagnostics.update(&[fan(0.0)]), [FanHealth::Ok]);
+        assert!(diagnostics.is_pending());
+        assert_eq!(diagnostics.update(&[fan(900.0)]), [FanHealth::Ok]);
+        assert_eq!(diagnostics.update(&[fan(2000.0)]), [FanHealth::Ok]);
+        assert!(!diagnostics.is_pending());
+
+        for _ in 1..3 {
+            assert_eq!(diagnostics.update(&[fan(0.0)]), [FanHealth::Ok]);
+        }
+        assert_eq!(diagnostics.update(&[fan(0.0)]), [FanHealth::Stalled]);
+        assert_eq!(
+            diagnostics.update(&[fan(6500.0)]),
+            [FanHealth::AboveMaximum]
+        );
+    }
+
+    #[test]
+    fn intel_aggregates() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let average = smc.fans().unwrap().average_percentage().unwrap().unwrap();
+        let fans = smc.fans().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let expected = (fans[0].percentage() + fans[1].percentage()) / 2.0;
+        assert!((average - expected).abs() < 1e-4);
+
+        let power = smc.cpu_power().unwrap();
+        assert_eq!(power.package(), Some(Watt(11.0)));
+        assert_eq!(power.uncore(), Some(Watt(2.75)));
+        assert_eq!(power.uncore_share(), Some(Percent(25.0)));
+    }
+
+    #[test]
+    fn aggregates_skip_missing_sensors() {
+        let cores = [
+            core(CoreKind::Performance, 0, 0.0),
+            core(CoreKind::Performance, 1, 52.5),
+            core(CoreKind::Efficiency, 0, 40.0),
+            core(CoreKind::Efficiency, 1, 0.0),
+        ];
+        let stats = CoreTemperatureStats::of(&cores).unwrap();
+        assert_eq!(stats.hottest, cores[1]);
+        assert_eq!(stats.min, Celsius(40.0));
+        assert_eq!(stats.mean, Celsius(46.25));
+        assert_eq!(stats.spread, Celsius(12.5));
+        assert_eq!(stats.count, 2);
+        assert_eq!(CoreTemperatureStats::of(&cores[3..]), None);
+
+        let fan = FanSpeed {
+            actual: Rpm(3000.0),
+            min: Rpm(1000.0),
+            max: Rpm(5000.0),
+            ..FanSpeed::default()
+        };
+        assert_eq!(
+            FanSpeed::average_percentage(&[fan, FanSpeed::default()]),
+            Some(50.0)
+        );
+
+        assert_eq!(CpuPower::default().package(), None);
+        let power = CpuPower {
+            total: Watt(4.0),
+            ..CpuPower::default()
+        };
+        assert_eq!(power.package(), Some(Watt(4.0)));
+        assert_eq!(power.uncore(), None);
+        assert_eq!(power.core_share(), None);
+        let dram_only = CpuPower {
+            dram: Watt(1.5),
+            ..CpuPower::default()
+        };
+        assert_eq!(dram_only.package(), None);
+    }
+
+    #[test]
+    fn intel_system_info() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let info = smc.system_info().unwrap();
+        assert_eq!(
+            info,
+            SystemInfo {
+                platform: Platform::Intel,
+                revision: SmcVersion {
+                    major: 2,
+                    minor: 0x44,
+                    build: 0xf,
+                    release: 6,
+                },
+                interface_version: SmcVersion::default(),
+                branch: String::from("j680"),
+                platform_id: String::from("j680"),
+            }
+        );
+        assert_eq!(info.revision.to_string(), "2.44f6");
+
+        // the system info is only read once, not for every snapshot
+        if let Backend::Fixture(fixture) = &mut smc.inner {
+            assert!(fixture
+                .write_key(*smc_key(b"RBr "), b"j999\0\0\0\0")
+                .is_ok());
+        }
+        assert_eq!(smc.system_info().unwrap(), info);
+        assert_eq!(smc.snapshot_of(&[]).unwrap().system_info, info);
+        smc.system_info = None;
+        assert_eq!(smc.system_info().unwrap().branch, "j999");
+    }
+
+    #[test]
+    fn intel_environment() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(
+            smc.ambient_light().unwrap(),
+            AmbientLight {
+                left: LightSensor {
+                    valid: true,
+                    high_gain: false,
+                    broadband: 300,
+                    infrared: 58,
+                },
+                right: LightSensor {
+                    valid: true,
+                    high_gain: false,
+                    broadband: 286,
+                    infrared: 54,
+                },
+            }
+        );
+        assert!(!smc.is_lid_closed().unwrap());
+        assert_eq!(smc.motion().unwrap(), Motion::default());
+    }
+
+    #[test]
+    fn intel_keyboard_backlight() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(smc.keyboard_backlight().unwrap(), Percent(20.0));
+        smc.set_keyboard_backlight(Percent(100.0)).unwrap();
+        assert_eq!(smc.keyboard_backlight().unwrap(), Percent(100.0));
+        smc.set_keyboard_backlight(Percent(-5.0)).unwrap();
+        assert_eq!(smc.keyboard_backlight().unwrap(), Percent(0.0));
+    }
+
+    #[test]
+    fn keyboard_backlight_is_missing_on_apple_silicon() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        assert_eq!(smc.keyboard_backlight().unwrap(), Percent(0.0));
+        let error = smc.set_keyboard_backlight(Percent(50.0)).unwrap_err();
+        assert!(matches!(&error, Error::KeyNotAvailable(id) if id == "keyboard.backlight"));
+        assert_eq!(
+            error.to_string(),
+            "keyboard.backlight is not available on this machine"
+        );
+    }
+
+    #[test]
+    fn apple_m1_discover() {
+        // Ts1S is not in the catalog, flags share the prefixes of the sensors
+        let dump = format!(
+            "{}  Ts1S  [flt ]  33.5 (bytes 00 00 06 42)\n  PSTF  [ui8 ]  1 (bytes 01)\n",
+            APPLE_M1
+        );
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), &dump);
+        let sensors = smc.discover().unwrap();
+        let find = |key: &str| sensors.iter().find(|sensor| sensor.key == key).cloned();
+
+        assert_eq!(sensors.len(), 23);
+        assert!(sensors.iter().all(|sensor| sensor.key != "BNum"));
+        assert_eq!(find("PSTF"), None);
+        assert_eq!(
+            find("PSTR"),
+            Some(DiscoveredSensor {
+                key: "PSTR".into(),
+                label: "System Total".into(),
+                known: true,
+                reading: Reading::Power(Watt(6.5)),
+            })
+        );
+        assert_eq!(
+            find("Ts1S"),
+            Some(DiscoveredSensor {
+                key: "Ts1S".into(),
+                label: "Temperature Ts1S".into(),
+                known: false,
+                reading: Reading::Temperature(Celsius(33.5)),
+            })
+        );
+        let labels = sensors
+            .iter()
+            .filter(|sensor| sensor.key.starts_with("Tp"))
+            .map(|sensor| sensor.label.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                "Performance Core 1",
+                "Performance Core 2",
+                "Efficiency Core 1",
+                "Performance Core 3",
+                "Performance Core 4",
+                "Performance Core 5",
+                "Performance Core 6",
+                "Efficiency Core 2",
+                "Performance Core 7",
+                "Performance Core 8",
+            ]
+        );
+    }
+
+    #[test]
+    fn intel_discover() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let sensors = smc.discover().unwrap();
+        let label = |key: &str| {
+            sensors
+                .iter()
+                .find(|sensor| sensor.key == key)
+                .map(|sensor| sensor.label.as_str())
+        };
+        assert_eq!(label("TC1C"), Some("CPU Core 1"));
+        assert_eq!(label("TC4C"), Some("CPU Core 4"));
+        assert_eq!(label("TG1D"), Some("GPU 2 Die"));
+        assert_eq!(label("TGDD"), Some("GPU 1 Die"));
+        assert!(sensors.iter().all(|sensor| sensor.known));
+
+        let fans = sensors
+            .into_iter()
+            .filter(|sensor| matches!(sensor.reading, Reading::Fan(_)))
+            .map(|sensor| (sensor.label, sensor.reading))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            fans,
+            [
+                ("Fan 1 Speed".to_string(), Reading::Fan(Rpm(2160.0))),
+                ("Fan 2 Speed".to_string(), Reading::Fan(Rpm(1999.0))),
+            ]
+        );
+    }
+
+    #[test]
+    fn intel_sensors() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let sensors = smc.sensors().unwrap();
+        let find = |key: &str| sensors.iter().find(|sensor| sensor.key == key).cloned();
+
+        assert_eq!(find("REV "), None);
+        assert_eq!(
+            find("F1Ac"),
+            Some(Sensor {
+                key: "F1Ac".into(),
+                id: "fan.actual".into(),
+                label: "Fan 2 Speed".into(),
+                kind: Category::Fan,
+                unit: Unit::Rpm,
+                value: 1999.0,
+                quality: Quality::Good,
+            })
+        );
+        assert_eq!(
+            find("TC1C").map(|sensor| (sensor.label, sensor.value)),
+            Some(("CPU Core 1".into(), 55.0))
+        );
+        assert_eq!(
+            find("MSLD").map(|sensor| (sensor.unit, sensor.value)),
+            Some((Unit::None, 0.0))
+        );
+        assert_eq!(
+            find("LKSB").map(|sensor| (sensor.unit, sensor.value)),
+            Some((Unit::Percent, 20.0))
+        );
+    }
+
+    #[test]
+    fn plausibility_flags_broken_sensors() {
+        let mut plausibility = Plausibility::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        for &value in &[-127.0, 0.0, 255.0] {
+            assert_eq!(
+                plausibility.check("TC0P", Unit::Celsius, value, at(0)),
+                Quality::Implausible
+            );
+        }
+        assert_eq!(
+            plausibility.check("F0Ac", Unit::Rpm, 0.0, at(0)),
+            Quality::Good
+        );
+        assert_eq!(
+            plausibility.check("B0FC", Unit::Percent, 255.0, at(0)),
+            Quality::Implausible
+        );
+
+        for &(key, unit, value) in &[
+            ("TC0P", Unit::Celsius, 42.0),
+            ("PSTR", Unit::Watt, 0.0),
+            ("F0Ac", Unit::Rpm, 2000.0),
+        ] {
+            assert_eq!(plausibility.check(key, unit, value, at(1)), Quality::Good);
+            assert_eq!(plausibility.check(key, unit, value, at(5)), Quality::Good);
+            let expected = if unit == Unit::Celsius {
+                Quality::Stuck
+            } else {
+                Quality::Good
+            };
+            assert_eq!(plausibility.check(key, unit, value, at(20)), expected);
+        }
+        assert_eq!(
+            plausibility.check("TC0P", Unit::Celsius, 42.25, at(21)),
+            Quality::Good
+        );
+        assert_eq!(
+            plausibility.check("TC0P", Unit::Celsius, 42.25, at(25)),
+            Quality::Good
+        );
+    }
 
-        cpus.max(1)
+    #[test]
+    fn intel_sensors_have_a_quality() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let sensors = smc.sensors().unwrap();
+        assert!(sensors
+            .iter()
+            .filter(|sensor| sensor.unit == Unit::Celsius)
+            .all(|sensor| sensor.quality == Quality::Good));
+        let mut sensors = sensors;
+        let later = Instant::now() + Plausibility::DEFAULT_STUCK_AFTER * 2;
+        smc.plausibility.check_sensors(&mut sensors, later);
+        let stuck = sensors.iter().find(|sensor| sensor.key == "TC0P").unwrap();
+        assert_eq!(stuck.quality, Quality::Stuck);
     }
 
+    /// A clock that only advances when the test sends a tick.
     #[derive(Debug)]
-    pub(super) struct SMCConnection {
-        conn: io_connect_t,
+    struct TickClock {
+        now: SystemTime,
+        ticks: mpsc::Receiver<()>,
     }
 
-    impl Drop for SMCConnection {
-        fn drop(&mut self) {
-            unsafe { _smc_close(self.conn) }
+    impl Clock for TickClock {
+        fn now(&mut self) -> SystemTime {
+            self.now
         }
-    }
 
-    impl SMCConnection {
-        pub(super) fn new() -> InternalResult<Self> {
-            let conn = unsafe { _smc_open() }?;
-            Ok(Self { conn })
+        fn sleep(&mut self, duration: Duration, stop: &mpsc::Receiver<()>) {
+            // keep checking for the stop signal, so that a failing test does not hang
+            while let Err(mpsc::RecvTimeoutError::Timeout) =
+                self.ticks.recv_timeout(Duration::from_millis(10))
+            {
+                if !matches!(stop.try_recv(), Err(mpsc::TryRecvError::Empty)) {
+                    return;
+                }
+            }
+            self.now += duration;
         }
+    }
 
-        pub(super) fn read_value<R>(&mut self, op: R) -> InternalResult<R::Out>
-        where
-            R: ReadAction,
-            R::Out: Default,
-        {
-            Ok(self.opt_read_value(op)?.unwrap_or_default())
-        }
-
-        pub(super) fn opt_read_value<R: ReadAction>(
-            &mut self,
-            op: R,
-        ) -> InternalResult<Option<R::Out>> {
-            let result = self.try_read_value(op);
-            match result {
-                Ok(result) => Ok(Some(result)),
-                Err(InternalError::_UnknownKey) => Ok(None),
-                Err(e) => Err(e),
-            }
-        }
-
-        fn try_read_value<R: ReadAction>(&mut self, op: R) -> InternalResult<R::Out> {
-            let key = *op.key();
-            let result = unsafe { _smc_read_key(self.conn, key) };
-            let result = result.map_err(|e| match e {
-                InternalError::_DataKeyError(tpe) => InternalError::DataError { key, tpe },
-                otherwise => otherwise,
-            })?;
-            let tpe = result.data_type;
-            let data = &result.bytes.0[..result.data_size as usize];
-            let data = DataValue::convert(data, tpe)?;
-            op.parse(data).map_err(|e| match e {
-                InternalError::_DataValueError => InternalError::DataError { key, tpe },
-                otherwise => otherwise,
-            })
-        }
+    #[test]
+    fn sampler_sends_samples_to_all_subscribers() {
+        let (tick, ticks) = mpsc::channel();
+        let start = std::time::UNIX_EPOCH + Duration::from_secs(1_600_000_000);
+        let interval = Duration::from_millis(250);
+        let mut builder = Sampler::builder()
+            .interval(interval)
+            .groups(&[SensorGroup::Fans])
+            .clock(TickClock { now: start, ticks });
+        let first = builder.subscribe();
+        let sampler = builder
+            .spawn(|| Ok(Smc::fixture(Platform::Intel, INTEL_MBP15)))
+            .unwrap();
+
+        let sample = first.recv().unwrap().unwrap();
+        assert_eq!(sample.timestamp, start);
+        assert_eq!(sample.fans.len(), 2);
+        assert_eq!(sample.cpu_temperature, CpuTemperatures::default());
+        assert!(sample.gpus.is_empty());
+
+        let second = sampler.subscribe();
+        tick.send(()).unwrap();
+        let sample = first.recv().unwrap().unwrap();
+        assert_eq!(sample.timestamp, start + interval);
+        assert_eq!(second.recv().unwrap().unwrap(), sample);
+
+        drop(first);
+        tick.send(()).unwrap();
+        let sample = second.recv().unwrap().unwrap();
+        assert_eq!(sample.timestamp, start + 2 * interval);
+
+        // the clock is still waiting for a tick, but stops anyway
+        sampler.stop();
+        drop(tick);
+        assert!(second.iter().all(|sample| sample.is_ok()));
+    }
 
-        pub(super) fn key_info<O: ReadAction>(&mut self, op: O) -> InternalResult<KeyInfo> {
-            let key = *op.key();
-            let result = unsafe { _smc_key_info(self.conn, key) };
-            result.map_err(|e| match e {
-                InternalError::_UnknownKey => InternalError::DataError {
-                    key,
-                    tpe: *smc_key(b"????"),
-                },
-                otherwise => otherwise,
-            })
-        }
+    #[test]
+    fn sampler_reports_connection_errors() {
+        let sampler = Sampler::builder().spawn(|| Err(Error::InsufficientPrivileges));
+        assert!(matches!(sampler, Err(Error::InsufficientPrivileges)));
+    }
 
-        pub(super) fn key_info_by_index(&mut self, index: u32) -> InternalResult<KeyInfo> {
-            let result = unsafe { _smc_key_index_info(self.conn, index) };
-            result.map_err(|e| match e {
-                InternalError::_UnknownKey => InternalError::DataError {
-                    key: *smc_key(b"????"),
-                    tpe: *smc_key(b"????"),
-                },
-                otherwise => otherwise,
+    #[test]
+    fn shared_smc_is_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync + Clone>() {}
+        assert_send_sync::<SharedSmc>();
+
+        let smc = SharedSmc::new(Smc::fixture(Platform::Intel, INTEL_MBP15));
+        let expected = Smc::fixture(Platform::Intel, INTEL_MBP15)
+            .cpu_temperature()
+            .unwrap();
+        let readers = (0..4)
+            .map(|_| {
+                let smc = smc.clone();
+                thread::spawn(move || smc.cpu_temperature())
             })
+            .collect::<Vec<_>>();
+        for reader in readers {
+            assert_eq!(reader.join().unwrap().unwrap(), expected);
         }
-    }
 
-    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
-    #[repr(u8)]
-    enum SMCReadCommand {
-        Data = 5,
-        ByIndex = 8,
-        KeyInfo = 9,
+        assert_eq!(smc.platform(), Platform::Intel);
+        assert_eq!(smc.fans().unwrap().len(), 2);
+        let (temperature, fans) = smc
+            .with(|smc| Ok::<_, Error>((smc.cpu_temperature()?, smc.fans()?.count())))
+            .unwrap();
+        assert_eq!(temperature, expected);
+        assert_eq!(fans, 2);
     }
 
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(C)]
-    struct SMCKeyData {
-        key: u32,
-        version: SMCKeyDataVersion,
-        p_limit_data: SMCKeyDataLimitData,
-        key_info: SMCKeyDataKeyInfo,
-        result: u8,
-        status: u8,
-        data8: u8,
-        data32: u32,
-        bytes: SMCBytes,
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_smc_runs_calls_on_its_thread() {
+        let smc = AsyncSmc::spawn(|| Ok(Smc::fixture(Platform::Intel, INTEL_MBP15)))
+            .await
+            .unwrap();
+        let mut expected = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(
+            smc.cpu_temperature().await.unwrap(),
+            expected.cpu_temperature().unwrap()
+        );
+        assert_eq!(smc.fans().await.unwrap().len(), 2);
+
+        let clone = smc.clone();
+        let platform = clone.call(|smc| Ok(smc.platform())).await;
+        assert_eq!(platform.unwrap(), Platform::Intel);
+        smc.shutdown().await;
+        assert!(matches!(
+            clone.cpu_temperature().await,
+            Err(Error::SmcNotAvailable)
+        ));
     }
 
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(C)]
-    struct SMCKeyDataVersion {
-        major: u8,
-        minor: u8,
-        build: u8,
-        reserved: u8,
-        release: u16,
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_smc_shuts_down_without_polling() {
+        let smc = AsyncSmc::spawn(|| Ok(Smc::fixture(Platform::Intel, INTEL_MBP15)))
+            .await
+            .unwrap();
+        let clone = smc.clone();
+        drop(smc.shutdown());
+        assert!(matches!(
+            clone.cpu_temperature().await,
+            Err(Error::SmcNotAvailable)
+        ));
     }
 
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(C)]
-    struct SMCKeyDataLimitData {
-        version: u16,
-        length: u16,
-        cpu_p_limit: u32,
-        gpu_p_limit: u32,
-        mem_p_limit: u32,
-    }
+    #[cfg(feature = "tokio")]
+    #[tokio::test(start_paused = true)]
+    async fn async_smc_streams_readings_until_shutdown() {
+        let smc = AsyncSmc::spawn(|| Ok(Smc::fixture(Platform::Intel, INTEL_MBP15)))
+            .await
+            .unwrap();
+        let mut readings = smc.interval_stream(Duration::from_millis(10), &[SensorGroup::Fans]);
+        for _ in 0..2 {
+            let reading = readings.next().await.unwrap().unwrap();
+            assert_eq!(reading.fans.len(), 2);
+            assert_eq!(reading.cpu_temperature, CpuTemperatures::default());
+        }
 
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(C)]
-    struct SMCKeyDataKeyInfo {
-        data_size: u32,
-        data_type: u32,
-        data_attributes: u8,
+        // block the SMC thread, so that the next reading is cancelled while it is queued
+        let (entered, blocking) = tokio::sync::oneshot::channel();
+        let (open, gate) = mpsc::channel();
+        let blocked = smc.clone();
+        let blocked = tokio::spawn(async move {
+            blocked
+                .call(move |_| {
+                    let _ = entered.send(());
+                    gate.recv().map_err(|_| Error::SmcNotAvailable)
+                })
+                .await
+        });
+        blocking.await.unwrap();
+        // the time is paused, so the timeout only elapses once the reading is queued
+        let cancelled = tokio::time::timeout(Duration::from_secs(1), readings.next()).await;
+        assert!(cancelled.is_err());
+        open.send(()).unwrap();
+        blocked.await.unwrap().unwrap();
+        assert!(readings.next().await.unwrap().is_ok());
+
+        smc.shutdown().await;
+        assert!(readings.next().await.is_none());
     }
 
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(transparent)]
-    struct SMCBytes([u8; 32]);
-
-    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
-    #[repr(C)]
-    struct SMCVal {
-        key: u32,
-        data_size: u32,
-        data_type: u32,
-        bytes: SMCBytes,
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn async_smc_reports_connection_errors() {
+        let smc = AsyncSmc::spawn(|| Err(Error::InsufficientPrivileges)).await;
+        assert!(matches!(smc, Err(Error::InsufficientPrivileges)));
     }
 
-    #[repr(C)]
-    struct __CFDictionary(c_void);
-
-    type CFDictionaryRef = *const __CFDictionary;
-    type CFMutableDictionaryRef = *mut __CFDictionary;
-
-    #[link(name = "IOKit", kind = "framework")]
-    extern "C" {
-        fn IOServiceMatching(name: *const u8) -> CFMutableDictionaryRef;
+    #[test]
+    fn snapshot_of_reads_only_the_given_groups() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let snapshot = smc
+            .snapshot_of(&[SensorGroup::Cpu, SensorGroup::Power])
+            .unwrap();
+        assert_eq!(snapshot.cpu_temperature, smc.cpu_temperature().unwrap());
+        assert_eq!(snapshot.cpu_power, smc.cpu_power().unwrap());
+        assert!(snapshot.fans.is_empty());
+        assert_eq!(snapshot.other_temperatures, OtherTemperatures::default());
+    }
 
-        fn IOServiceGetMatchingService(
-            master_port: mach_port_t,
-            matching: CFDictionaryRef,
-        ) -> io_service_t;
+    #[test]
+    fn apple_m1_sensors_report_shared_keys_once() {
+        let mut smc = Smc::fixture(Platform::AppleSilicon(1), APPLE_M1);
+        let sensors = smc.sensors().unwrap();
+        let shared = sensors
+            .iter()
+            .filter(|sensor| sensor.key == "PGTR")
+            .map(|sensor| sensor.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(shared, ["power.cpu.gfx"]);
+        assert!(sensors.iter().all(|sensor| !sensor.key.starts_with("TC")));
+    }
 
-        fn IOServiceOpen(
-            service: io_service_t,
-            owning_task: task_port_t,
-            r#type: u32,
-            connect: *const io_connect_t,
-        ) -> kern_return_t;
+    #[test]
+    fn intel_snapshot() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let before = SystemTime::now();
+        let snapshot = smc.snapshot().unwrap();
+        assert!(snapshot.timestamp >= before);
+        assert_eq!(snapshot.system_info, smc.system_info().unwrap());
+        assert_eq!(snapshot.cpu_temperature, smc.cpu_temperature().unwrap());
+        assert_eq!(snapshot.fans.len(), 2);
+        assert_eq!(snapshot.gpus.len(), 2);
+        assert_eq!(snapshot.power_system_total, Watt(24.0));
+        assert_eq!(snapshot.keyboard_backlight, Percent(20.0));
+
+        let unchanged = SmcSnapshot {
+            timestamp: snapshot.timestamp,
+            ..smc.snapshot().unwrap()
+        };
+        assert_eq!(unchanged, snapshot);
+    }
 
-        fn IOServiceClose(connect: io_connect_t) -> kern_return_t;
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_includes_units() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let fans = smc.fans().unwrap().collect::<Result<Vec<_>>>().unwrap();
+        let json = serde_json::to_value(fans[0]).unwrap();
+        assert_eq!(json["actual"], serde_json::json!({ "rpm": 2160.0 }));
+        assert_eq!(json["mode"], serde_json::json!("auto"));
+
+        let snapshot = smc.snapshot().unwrap();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let parsed = serde_json::from_str::<SmcSnapshot>(&json).unwrap();
+        assert_eq!(parsed, snapshot);
+
+        let unknown_unit = serde_json::from_str::<Celsius>(r#"{ "fahrenheit": 42.0 }"#);
+        assert!(unknown_unit.is_err());
+    }
 
-        fn IOConnectCallStructMethod(
-            connection: mach_port_t,
-            selector: u32,
-            input: *const c_void,
-            input_size: usize,
-            output: *mut c_void,
-            output_size: *mut usize,
-        ) -> kern_return_t;
+    #[test]
+    #[cfg(feature = "uom")]
+    fn uom_temperatures_keep_their_offset() {
+        use uom::si::{
+            f64::{TemperatureInterval, ThermodynamicTemperature},
+            temperature_interval,
+            thermodynamic_temperature::kelvin,
+        };
 
-        fn IOObjectRelease(object: io_object_t) -> kern_return_t;
+        let kelvin_of = |t: ThermodynamicTemperature| t.get::<kelvin>();
+        assert_eq!(kelvin_of(Celsius(0.0).into()), 273.15);
+        assert!(kelvin_of(Celsius(-273.15).into()).abs() < 1e-4);
+        assert!((kelvin_of(Fahrenheit(32.0).into()) - 273.15).abs() < 1e-9);
+        assert_eq!(
+            Celsius::from(ThermodynamicTemperature::from(Fahrenheit(-40.0))),
+            Celsius(-40.0)
+        );
+        assert_eq!(
+            Fahrenheit::from(ThermodynamicTemperature::from(Celsius(100.0))),
+            Fahrenheit(212.0)
+        );
+        assert_eq!(
+            Kelvin::from(ThermodynamicTemperature::from(Celsius(0.0))),
+            Kelvin(273.15)
+        );
 
-        fn mach_task_self() -> mach_port_t;
+        // adding an interval does not apply the offset again
+        let warmer = ThermodynamicTemperature::from(Celsius(40.0))
+            + TemperatureInterval::new::<temperature_interval::kelvin>(10.0);
+        assert_eq!(Celsius::from(warmer), Celsius(50.0));
     }
 
-    unsafe fn _smc_open() -> InternalResult<io_connect_t> {
-        let matching_dictionary = IOServiceMatching(b"AppleSMC\0".as_ptr());
-        let device = IOServiceGetMatchingService(MASTER_PORT_DEFAULT, matching_dictionary);
-
-        if device.is_null() {
-            return Err(InternalError::SmcNotFound);
+    #[test]
+    #[cfg(feature = "uom")]
+    fn uom_conversions_are_lossless() {
+        use uom::si::f64::*;
+
+        for &value in &[0.0, -0.5, 42.25, 1e-3, -40.0, 100.0, -273.15, 12345.678] {
+            assert_eq!(
+                Celsius::from(ThermodynamicTemperature::from(Celsius(value))),
+                Celsius(value)
+            );
+            assert_eq!(
+                Fahrenheit::from(ThermodynamicTemperature::from(Fahrenheit(value))),
+                Fahrenheit(value)
+            );
         }
-
-        let result: kern_return_t;
-        let conn: io_connect_t = MASTER_PORT_DEFAULT;
-        result = IOServiceOpen(device, mach_task_self(), 0, &conn);
-        let _ = IOObjectRelease(device);
-
-        if result != RETURN_SUCCESS {
-            return Err(InternalError::SmcFailedToOpen(result));
+        for &value in &[
+            0.0,
+            -0.5,
+            42.25,
+            1e-3,
+            12345.678,
+            f32::MAX,
+            f32::MIN_POSITIVE,
+        ] {
+            assert_eq!(Rpm::from(AngularVelocity::from(Rpm(value))), Rpm(value));
+            assert_eq!(Watt::from(Power::from(Watt(value))), Watt(value));
+            assert_eq!(
+                Volt::from(ElectricPotential::from(Volt(value))),
+                Volt(value)
+            );
         }
-
-        Ok(conn)
+        for &value in &[0, -1, 4200, i32::MAX, i32::MIN] {
+            let current = ElectricCurrent::from(MilliAmpere(value));
+            assert_eq!(MilliAmpere::from(current), MilliAmpere(value));
+        }
+        for &value in &[0, 1, 4200, u32::MAX] {
+            let charge = ElectricCharge::from(MilliAmpereHours(value));
+            assert_eq!(MilliAmpereHours::from(charge), MilliAmpereHours(value));
+        }
+        assert_eq!(
+            Ampere::from(ElectricCurrent::from(MilliAmpere(1500))),
+            Ampere(1.5)
+        );
     }
 
-    unsafe fn _smc_close(conn: io_connect_t) {
-        let _ = IOServiceClose(conn);
+    #[test]
+    fn parse_temperatures() {
+        assert_eq!("85".parse(), Ok(Celsius(85.0)));
+        assert_eq!("85C".parse(), Ok(Celsius(85.0)));
+        assert_eq!(" 85 °c ".parse(), Ok(Celsius(85.0)));
+        assert_eq!("85℃".parse(), Ok(Celsius(85.0)));
+        assert_eq!("85ºC".parse(), Ok(Celsius(85.0)));
+        assert_eq!("-40°F".parse(), Ok(Celsius(-40.0)));
+        assert_eq!("185℉".parse(), Ok(Celsius(85.0)));
+        assert_eq!("273.15 K".parse(), Ok(Celsius(0.0)));
+        assert_eq!("273.15 \u{212a}".parse(), Ok(Celsius(0.0)));
+        assert_eq!("185".parse(), Ok(Fahrenheit(185.0)));
+        assert_eq!("85 celsius".parse(), Ok(Fahrenheit(185.0)));
+        assert_eq!("0°C".parse(), Ok(Kelvin(273.15)));
     }
 
-    unsafe fn _smc_read_key(conn: mach_port_t, key: u32) -> InternalResult<SMCVal> {
-        let mut input = SMCKeyData::default();
-        input.key = key;
-        input.data8 = SMCReadCommand::KeyInfo as u8;
+    #[test]
+    fn parse_units() {
+        assert_eq!("3000rpm".parse(), Ok(Rpm(3000.0)));
+        assert_eq!("3000 RPM".parse(), Ok(Rpm(3000.0)));
+        assert_eq!("45W".parse(), Ok(Watt(45.0)));
+        assert_eq!("500mW".parse(), Ok(Watt(0.5)));
+        assert_eq!("12.5 V".parse(), Ok(Volt(12.5)));
+        assert_eq!("1200mV".parse(), Ok(Volt(1.2)));
+        assert_eq!("1.5A".parse(), Ok(Ampere(1.5)));
+        assert_eq!("1500 mA".parse(), Ok(Ampere(1.5)));
+        assert_eq!("1500".parse(), Ok(MilliAmpere(1500)));
+        assert_eq!("2 amps".parse(), Ok(MilliAmpere(2000)));
+        assert_eq!("50%".parse(), Ok(Percent(50.0)));
+        assert_eq!("1e3W".parse(), Ok(Watt(1000.0)));
+        assert_eq!("2.5E-3 kW".parse(), Ok(Watt(2.5)));
+        assert_eq!("1e+3mA".parse(), Ok(Ampere(1.0)));
+    }
 
-        let mut output = SMCKeyData::default();
-        _smc_call(conn, &input, &mut output)?;
+    #[test]
+    fn parse_unit_errors() {
+        assert_eq!("".parse::<Celsius>(), Err(ParseUnitError::Empty));
+        assert_eq!(
+            "hot".parse::<Celsius>(),
+            Err(ParseUnitError::InvalidNumber {
+                input: "hot".into()
+            })
+        );
+        assert_eq!(
+            "1.2.3W".parse::<Watt>(),
+            Err(ParseUnitError::InvalidNumber {
+                input: "1.2.3W".into()
+            })
+        );
+        let error = "45 rpm".parse::<Watt>().unwrap_err();
+        assert_eq!(
+            error,
+            ParseUnitError::UnknownUnit {
+                input: "45 rpm".into(),
+                unit: "rpm".into(),
+                expected: "W, mW, kW",
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Unknown unit `rpm` in `45 rpm`, expected one of W, mW, kW"
+        );
+        assert!("85°X".parse::<Fahrenheit>().is_err());
+    }
 
-        let data_type = output.key_info.data_type;
-        let data_size = output.key_info.data_size;
+    #[test]
+    fn default_thresholds_depend_on_sensor_and_platform() {
+        let m1 = Platform::AppleSilicon(1);
+        let die = DefaultThresholds.temperature("cpu.core.performance", m1, "");
+        assert_eq!(die[3], Celsius(100.0));
+        let die = DefaultThresholds.temperature("cpu.core", Platform::Intel, "");
+        assert_eq!(die[3], Celsius(95.0));
+        let palm_rest = DefaultThresholds.temperature("palm_rest.2", m1, "");
+        assert!(palm_rest[3] < die[0]);
+        assert_eq!(
+            DefaultThresholds.temperature("cpu.proximity", m1, ""),
+            Celsius::thresholds()
+        );
+        assert_eq!(
+            DefaultThresholds.power("power.cpu.core", m1, "")[3],
+            Watt(30.0)
+        );
+        assert_eq!(
+            DefaultThresholds.power("power.cpu.core", Platform::Intel, ""),
+            Watt::thresholds()
+        );
+    }
 
-        if data_size > 32 {
-            return Err(InternalError::_DataKeyError(data_type));
-        }
+    #[test]
+    fn custom_thresholds_override_the_policy() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(smc.temperature_thresholds("gpu.die")[3], Celsius(95.0));
+        let hot = [Celsius(1.0), Celsius(2.0), Celsius(3.0), Celsius(4.0)];
+        let cold = [Celsius(-4.0), Celsius(-3.0), Celsius(-2.0), Celsius(-1.0)];
+        smc.set_threshold_policy(
+            CustomThresholds::new()
+                .override_temperature("gpu", hot)
+                .override_temperature("gpu.die", cold)
+                .override_power("power", [Watt(1.0), Watt(2.0), Watt(3.0), Watt(4.0)]),
+        );
+        assert_eq!(smc.temperature_thresholds("gpu.die"), cold);
+        assert_eq!(smc.temperature_thresholds("gpu.proximity"), hot);
+        assert_eq!(smc.temperature_thresholds("gpu_fan"), Celsius::thresholds());
+        assert_eq!(smc.power_thresholds("power.gpu")[3], Watt(4.0));
+    }
 
-        input.key_info.data_size = data_size;
-        input.data8 = SMCReadCommand::Data as u8;
+    #[test]
+    fn intel_thermal_status() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let status = smc.thermal_status().unwrap();
+        assert_eq!(status.state, ThermalState::Nominal);
+        assert_eq!(status.cause, None);
+    }
 
-        _smc_call(conn, &input, &mut output)?;
+    #[test]
+    fn power_limits_throttle_the_thermal_status() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        assert_eq!(smc.power_limits().unwrap(), PowerLimits::default());
 
-        let val = SMCVal {
-            key,
-            data_size,
-            data_type,
-            bytes: output.bytes,
+        let limits = PowerLimits {
+            version: 1,
+            cpu: 0,
+            gpu: 0,
+            memory: 3,
         };
-
-        Ok(val)
+        if let Backend::Fixture(fixture) = &mut smc.inner {
+            fixture.power_limits = Some(limits);
+        }
+        let read = smc.power_limits().unwrap();
+        assert_eq!(read, limits);
+        assert!(!read.cpu_limited());
+        assert!(read.memory_limited());
+        assert!(read.is_limited());
+
+        let status = smc.thermal_status().unwrap();
+        assert_eq!(status.state, ThermalState::Serious);
+        assert_eq!(status.cause, Some(ThermalCause::PowerLimit(limits)));
     }
 
-    unsafe fn _smc_key_info(conn: mach_port_t, key: u32) -> InternalResult<KeyInfo> {
-        let mut input = SMCKeyData::default();
-        input.key = key;
-        input.data8 = SMCReadCommand::KeyInfo as u8;
+    #[test]
+    fn thermal_status_reports_the_hottest_reading() {
+        let thresholds = |id: &str| DefaultThresholds.temperature(id, Platform::Intel, "");
+        let cpu = CpuTemperatures {
+            proximity: Celsius(50.0),
+            die: Celsius(72.0),
+            ..CpuTemperatures::default()
+        };
+        let gpu = GpuTemperatures {
+            die: Celsius(88.0),
+            ..GpuTemperatures::default()
+        };
+        let fan = FanSpeed {
+            actual: Rpm(5000.0),
+            min: Rpm(1000.0),
+            max: Rpm(5000.0),
+            ..FanSpeed::default()
+        };
+        let limits = PowerLimits {
+            cpu: 1,
+            ..PowerLimits::default()
+        };
 
-        let mut output = SMCKeyData::default();
-        _smc_call(conn, &input, &mut output)?;
+        let status = ThermalStatus::classify(&cpu, &gpu, &[], &limits, thresholds);
+        assert_eq!(status.state, ThermalState::Serious);
+        assert_eq!(
+            status.cause,
+            Some(ThermalCause::Temperature {
+                id: "gpu.die".into(),
+                temperature: Celsius(88.0),
+            })
+        );
 
-        let data_type = output.key_info.data_type;
-        let data_size = output.key_info.data_size;
+        let status = ThermalStatus::classify(
+            &cpu,
+            &GpuTemperatures::default(),
+            &[FanSpeed::default(), fan],
+            &limits,
+            thresholds,
+        );
+        assert_eq!(status.state, ThermalState::Serious);
+        assert_eq!(
+            status.cause,
+            Some(ThermalCause::Fan {
+                index: 1,
+                percentage: 100.0
+            })
+        );
 
-        Ok(KeyInfo {
-            key,
-            data_type,
-            data_size,
-        })
+        let status = ThermalStatus::classify(
+            &cpu,
+            &GpuTemperatures::default(),
+            &[],
+            &PowerLimits::default(),
+            thresholds,
+        );
+        assert_eq!(status.state, ThermalState::Fair);
     }
 
-    unsafe fn _smc_key_index_info(conn: mach_port_t, index: u32) -> InternalResult<KeyInfo> {
-        let mut input = SMCKeyData::default();
-        input.data8 = SMCReadCommand::ByIndex as u8;
-        input.data32 = index;
+    #[test]
+    fn builtin_catalog_has_all_profile_ids() {
+        let catalog = Catalog::builtin();
+        for profile in &[&INTEL_PROFILE, &APPLE_SILICON_PROFILE] {
+            let cores = match profile.cpu_cores {
+                CoreSensors::Indexed(id) => vec![id],
+                CoreSensors::Listed {
+                    performance,
+                    efficiency,
+                } => vec![performance, efficiency],
+            };
+            let ids = [
+                &profile.cpu_proximity,
+                &profile.cpu_die,
+                &profile.cpu_graphics,
+                &profile.cpu_system_agent,
+                &profile.gpu_proximity,
+                &profile.gpu_die,
+                &profile.cpu_core_power,
+                &profile.cpu_dram_power,
+                &profile.cpu_gfx_power,
+                &profile.cpu_rail_power,
+                &profile.cpu_total_power,
+                &profile.gpu_power,
+            ]
+            .iter()
+            .flat_map(|sensors| sensors.ids.iter().copied())
+            .chain(cores)
+            .collect::<Vec<_>>();
+            for id in ids {
+                assert!(catalog.label(id).is_some(), "missing id {}", id);
+            }
+        }
+    }
 
-        let mut output = SMCKeyData::default();
-        _smc_call(conn, &input, &mut output)?;
+    #[test]
+    fn catalog_reports_invalid_lines() {
+        let error =
+            Catalog::parse("# comment\n\nTC0P | cpu.proximity | cpu | K | CPU | |").unwrap_err();
+        assert!(matches!(
+            error,
+            CatalogError::InvalidEntry {
+                line: 3,
+                reason: "unknown unit"
+            }
+        ));
+        assert!(Catalog::parse("TC0PP | cpu.proximity | cpu | °C | CPU | |").is_err());
+        assert!(Catalog::parse("TC0P | cpu.proximity | cpu | °C | CPU |").is_err());
+    }
 
-        let key = output.key;
-        let data_type = output.key_info.data_type;
-        let data_size = output.key_info.data_size;
+    #[test]
+    fn catalog_model_restrictions() {
+        let catalog = Catalog::parse(
+            "Tf04 | cpu.core.performance | cpu | °C | P-Core # | m3+ |
+             Tp01 | cpu.core.performance | cpu | °C | P-Core # | m1 |
+             TC0P | cpu.proximity | cpu | °C | CPU | intel MacBookPro15 |",
+        )
+        .unwrap();
+        let [m3, m1, intel] = [
+            &catalog.entries()[0],
+            &catalog.entries()[1],
+            &catalog.entries()[2],
+        ];
+        assert!(m3.applies_to(Platform::AppleSilicon(4), ""));
+        assert!(!m3.applies_to(Platform::AppleSilicon(2), ""));
+        assert!(m1.applies_to(Platform::AppleSilicon(0), ""));
+        assert!(intel.applies_to(Platform::Intel, ""));
+        assert!(intel.applies_to(Platform::AppleSilicon(1), "MacBookPro15,1"));
+        assert!(!intel.applies_to(Platform::AppleSilicon(1), "Mac14,2"));
+
+        let catalog = Catalog::parse(
+            "TC0P | cpu.proximity | cpu | °C | CPU | MacBookPro1 |
+             TC0P | cpu.proximity | cpu | °C | CPU | MacBookPro15,1 |",
+        )
+        .unwrap();
+        let [family, model] = [&catalog.entries()[0], &catalog.entries()[1]];
+        assert!(family.applies_to(Platform::Intel, "MacBookPro1,2"));
+        assert!(!family.applies_to(Platform::Intel, "MacBookPro15,1"));
+        assert!(!family.applies_to(Platform::Intel, "MacBookPro16,2"));
+        assert!(model.applies_to(Platform::Intel, "MacBookPro15,1"));
+        assert!(!model.applies_to(Platform::Intel, "MacBookPro15,2"));
+    }
 
-        Ok(KeyInfo {
-            key,
-            data_type,
-            data_size,
-        })
+    #[test]
+    fn model_specific_labels_only_apply_to_their_model() {
+        let mut catalog = Catalog::builtin();
+        catalog.extend(
+            Catalog::parse("TC0P | cpu.proximity | cpu | °C | CPU Heatsink | MacBookPro15 |")
+                .unwrap(),
+        );
+        let label = |platform, model| catalog.label_for("cpu.proximity", platform, model);
+        assert_eq!(
+            label(Platform::Intel, "MacBookPro15,1"),
+            Some("CPU Heatsink")
+        );
+        assert_eq!(
+            label(Platform::Intel, "MacBookPro16,1"),
+            Some("CPU Proximity")
+        );
+        assert_eq!(label(Platform::AppleSilicon(1), ""), Some("CPU Proximity"));
+        // ids without an entry for the machine still have a label
+        assert_eq!(
+            catalog.label_for("power.cpu.total", Platform::AppleSilicon(1), ""),
+            Some("CPU Total")
+        );
+        assert_eq!(catalog.label_for("unknown", Platform::Intel, ""), None);
     }
 
-    unsafe fn _smc_call(
-        conn: mach_port_t,
-        input: &SMCKeyData,
-        output: &mut SMCKeyData,
-    ) -> InternalResult<()> {
-        let mut output_size = size_of::<SMCKeyData>();
+    #[test]
+    fn derived_entries_only_provide_labels() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        let derived = smc
+            .catalog()
+            .entries()
+            .iter()
+            .filter(|entry| entry.is_derived())
+            .map(|entry| entry.id.clone())
+            .collect::<Vec<_>>();
+        assert!(derived.iter().any(|id| id == "fan.health"));
+        assert_eq!(
+            smc.catalog().label_for("limit.cpu", Platform::Intel, ""),
+            Some("CPU Limited")
+        );
+        let sensors = smc.sensors().unwrap();
+        assert!(sensors.iter().all(|sensor| !derived.contains(&sensor.id)));
+    }
 
-        let result = IOConnectCallStructMethod(
-            conn,
-            KERNEL_INDEX_SMC,
-            input as *const _ as *const c_void,
-            size_of::<SMCKeyData>(),
-            output as *mut _ as *mut c_void,
-            &mut output_size,
+    #[test]
+    fn extended_catalog_takes_precedence() {
+        let mut smc = Smc::fixture(Platform::Intel, INTEL_MBP15);
+        smc.extend_catalog(
+            Catalog::parse("TC1C | cpu.proximity | cpu | °C | CPU Proximity | MacBookPro99 |")
+                .unwrap(),
         );
+        assert_eq!(smc.cpu_temperature().unwrap().proximity, Celsius(52.25));
 
-        if result == RETURN_NOT_PRIVILEGED {
-            return Err(InternalError::NotPrivlileged);
-        }
-        if result != RETURN_SUCCESS {
-            return Err(InternalError::UnknownSmc(result, output.result));
-        }
-        if output.result == 132 {
-            return Err(InternalError::_UnknownKey);
-        }
+        smc.model = String::from("MacBookPro99,1");
+        assert_eq!(smc.cpu_temperature().unwrap().proximity, Celsius(55.0));
+        assert_eq!(smc.catalog().entries_by_id("cpu.proximity").count(), 2);
+    }
 
-        Ok(())
+    #[test]
+    fn apple_silicon_generation() {
+        assert_eq!(cffi::apple_silicon_generation(b"Apple M1"), 1);
+        assert_eq!(cffi::apple_silicon_generation(b"Apple M2 Pro"), 2);
+        assert_eq!(cffi::apple_silicon_generation(b"Apple M10 Max"), 10);
+        assert_eq!(cffi::apple_silicon_generation(b"Apple processor"), 0);
+        assert_eq!(cffi::apple_silicon_generation(b"Intel(R) Core(TM) i7"), 0);
     }
 }
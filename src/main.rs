@@ -32,10 +32,32 @@
 //! > macstats power
 //! ... only power info
 //!
+//! > macstats voltage
+//! ... only voltages
+//!
+//! > macstats current
+//! ... only currents
+//!
+//! > macstats env
+//! ... only ambient light, lid and motion sensors
+//!
+//! > macstats discover
+//! ... only sensors that are not in the catalog
+//!
+//! > macstats backlight
+//! ... only the keyboard backlight level
+//!
+//! > macstats backlight 50
+//! ... set the keyboard backlight to 50%
+//!
 //! > macstats debug
 //! ... dump all knwon symbols
 //! ```
 //!
+//! Labels are taken from the catalog of known SMC keys. To add keys for models that
+//! are not supported yet, point the `MACSTATS_CATALOG` environment variable to a catalog file
+//! in the format of the [built-in catalog](https://github.com/knutwalker/macstats/blob/main/macsmc/src/catalog.txt).
+//!
 //! The functionality is provided by [`macsmc`](https://docs.rs/macsmc/*/macsmc/).
 #![warn(anonymous_parameters)]
 #![warn(missing_copy_implementations)]
@@ -48,7 +70,10 @@
 #![warn(unused_results)]
 #![warn(variant_size_differences)]
 
-use macsmc::{Celsius, Error as SmcError, Smc, Watt};
+use macsmc::{
+    Catalog, CatalogError, Celsius, CoreKind, Error as SmcError, FanDiagnostics, FanHealth,
+    ParseUnitError, Percent, Smc, ThermalCause, ThermalState, Unit, Watt,
+};
 use std::{
     cmp::Ordering,
     env,
@@ -64,15 +89,19 @@ type Result<T> = std::result::Result<T, Error>;
 #[derive(Debug)]
 enum Error {
     Smc(SmcError),
+    Catalog(CatalogError),
     UnknownStatsSelector(String),
+    InvalidBacklightLevel(String),
+    UnparsableBacklightLevel(ParseUnitError),
 }
 
 impl StdError for Error {
     fn source(&self) -> Option<&(dyn StdError + 'static)> {
-        if let Error::Smc(smc) = self {
-            Some(smc)
-        } else {
-            None
+        match self {
+            Error::Smc(smc) => Some(smc),
+            Error::Catalog(catalog) => Some(catalog),
+            Error::UnparsableBacklightLevel(parse) => Some(parse),
+            _ => None,
         }
     }
 }
@@ -81,7 +110,14 @@ impl Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::Smc(e) => write!(f, "{}", e),
+            Error::Catalog(e) => write!(f, "{}", e),
             Error::UnknownStatsSelector(cmd) => write!(f, "The command `{}` is not known", cmd),
+            Error::InvalidBacklightLevel(level) => write!(
+                f,
+                "The backlight level `{}` is not a percentage between 0 and 100",
+                level
+            ),
+            Error::UnparsableBacklightLevel(e) => write!(f, "Invalid backlight level: {}", e),
         }
     }
 }
@@ -92,6 +128,12 @@ impl From<SmcError> for Error {
     }
 }
 
+impl From<CatalogError> for Error {
+    fn from(e: CatalogError) -> Self {
+        Error::Catalog(e)
+    }
+}
+
 #[doc(hidden)]
 fn main() {
     if let Err(e) = run() {
@@ -101,7 +143,7 @@ fn main() {
 
 #[doc(hidden)]
 #[derive(Debug, Copy, Clone)]
-#[repr(u8)]
+#[repr(u16)]
 enum Printables {
     Cpu = 1,
     Gpu = 2,
@@ -109,7 +151,11 @@ enum Printables {
     Fan = 8,
     Battery = 16,
     Power = 32,
-    Debug = 64,
+    Voltage = 64,
+    Current = 128,
+    Env = 256,
+    Discovered = 512,
+    Debug = 1024,
 }
 
 #[doc(hidden)]
@@ -118,39 +164,65 @@ fn run() -> Result<()> {
 
     let mut args = env::args();
     let _ = args.next().expect("missing program name");
+    let mut args = args.peekable();
+
+    if args.peek().map(String::as_str) == Some("backlight") {
+        let _ = args.next();
+        return run_backlight(args.next());
+    }
 
     let mut commands = 0;
     for item in args {
         match &item[..] {
-            "temp" | "temps" => commands |= Cpu as u8 | Gpu as u8 | Other as u8,
-            "cpu" | "CPU" | "hot" => commands |= Cpu as u8,
-            "gpu" | "GPU" => commands |= Gpu as u8,
-            "other" | "others" => commands |= Other as u8,
-            "fan" | "fans" | "speed" | "fast" => commands |= Fan as u8,
-            "battery" | "batt" | "ac" => commands |= Battery as u8,
-            "power" => commands |= Power as u8,
-            "debug" => commands |= Debug as u8,
+            "temp" | "temps" => commands |= Cpu as u16 | Gpu as u16 | Other as u16,
+            "cpu" | "CPU" | "hot" => commands |= Cpu as u16,
+            "gpu" | "GPU" => commands |= Gpu as u16,
+            "other" | "others" => commands |= Other as u16,
+            "fan" | "fans" | "speed" | "fast" => commands |= Fan as u16,
+            "battery" | "batt" | "ac" => commands |= Battery as u16,
+            "power" => commands |= Power as u16,
+            "voltage" | "voltages" | "volt" => commands |= Voltage as u16,
+            "current" | "currents" | "amp" => commands |= Current as u16,
+            "env" | "environment" | "lid" => commands |= Env as u16,
+            "discover" | "unknown" => commands |= Discovered as u16,
+            "debug" => commands |= Debug as u16,
             "all" | "EVERYTHING" => {
-                commands |=
-                    Cpu as u8 | Gpu as u8 | Other as u8 | Fan as u8 | Battery as u8 | Power as u8
+                commands |= Cpu as u16
+                    | Gpu as u16
+                    | Other as u16
+                    | Fan as u16
+                    | Battery as u16
+                    | Power as u16
+                    | Voltage as u16
+                    | Current as u16
+                    | Env as u16
+                    | Discovered as u16
             }
             _ => return Err(Error::UnknownStatsSelector(item)),
         }
     }
 
     if commands == 0 {
-        commands = Cpu as u8 | Fan as u8 | Battery as u8 | Power as u8
+        commands = Cpu as u16 | Fan as u16 | Battery as u16 | Power as u16
     }
 
-    let mut smc = Smc::connect()?;
-    if commands & Debug as u8 != 0 {
+    let mut smc = connect()?;
+    if commands & Debug as u16 != 0 {
         print_all_keys(&mut smc)?;
         return Ok(());
     }
 
+    print_thermal_status(&mut smc)?;
+    println!();
+    println!();
+
     let mut printed_something = false;
-    for &item in [Cpu, Gpu, Other, Fan, Battery, Power].iter() {
-        if commands & item as u8 != 0 {
+    for &item in [
+        Cpu, Gpu, Other, Fan, Battery, Power, Voltage, Current, Env, Discovered,
+    ]
+    .iter()
+    {
+        if commands & item as u16 != 0 {
             if printed_something {
                 println!();
                 println!();
@@ -162,6 +234,10 @@ fn run() -> Result<()> {
                 Fan => print_fan_speeds(&mut smc)?,
                 Battery => print_battery_info(&mut smc)?,
                 Power => print_power_consumption(&mut smc)?,
+                Voltage => print_voltages(&mut smc)?,
+                Current => print_currents(&mut smc)?,
+                Env => print_environment(&mut smc)?,
+                Discovered => print_discovered(&mut smc)?,
                 Debug => {}
             }
             printed_something = true;
@@ -171,19 +247,100 @@ fn run() -> Result<()> {
     Ok(())
 }
 
+#[doc(hidden)]
+fn connect() -> Result<Smc> {
+    let mut smc = Smc::connect()?;
+    if let Some(path) = env::var_os("MACSTATS_CATALOG") {
+        smc.extend_catalog(Catalog::load(path)?);
+    }
+    Ok(smc)
+}
+
+#[doc(hidden)]
+fn run_backlight(level: Option<String>) -> Result<()> {
+    let mut smc = connect()?;
+    if let Some(level) = level {
+        let percent = level
+            .parse::<Percent>()
+            .map_err(Error::UnparsableBacklightLevel)?;
+        if !(0.0..=100.0).contains(&*percent) {
+            return Err(Error::InvalidBacklightLevel(level));
+        }
+        smc.set_keyboard_backlight(percent)?;
+    }
+    let backlight = smc.keyboard_backlight()?;
+    print_percentage(label(&smc, "keyboard.backlight"), *backlight);
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn print_thermal_status(smc: &mut Smc) -> Result<()> {
+    let status = smc.thermal_status()?;
+    let color = match status.state {
+        ThermalState::Nominal => '2',
+        ThermalState::Fair => '3',
+        ThermalState::Serious => '9',
+        ThermalState::Critical => '1',
+    };
+    let cause = match status.cause {
+        Some(ThermalCause::Temperature { id, temperature }) => {
+            format!(" ({} at {:.2})", label(smc, &id), temperature)
+        }
+        Some(ThermalCause::Fan { index, percentage }) => {
+            let label = numbered_label(smc, "fan.actual", index + 1);
+            format!(" ({} at {:.0}%)", label, percentage)
+        }
+        Some(ThermalCause::PowerLimit(limits)) => {
+            let limited = [
+                ("limit.cpu", limits.cpu_limited()),
+                ("limit.gpu", limits.gpu_limited()),
+                ("limit.memory", limits.memory_limited()),
+            ]
+            .iter()
+            .filter(|(_, limited)| *limited)
+            .map(|(id, _)| label(smc, id))
+            .collect::<Vec<_>>();
+            format!(" ({})", limited.join(", "))
+        }
+        None => String::new(),
+    };
+    print_line(
+        label(smc, "thermal.state"),
+        format_args!("\x1B[38;5;{}m{}\x1B[0m{}", color, status.state, cause),
+    );
+
+    Ok(())
+}
+
 #[doc(hidden)]
 fn print_cpu_temps(smc: &mut Smc) -> Result<()> {
     println!("--- CPU Temperatures [cpu] ---");
     println!();
     let cpu_temp = smc.cpu_temperature()?;
-    print_temp("CPU Proximity", cpu_temp.proximity);
-    print_temp("CPU Die", cpu_temp.die);
-    print_temp("CPU Graphics", cpu_temp.graphics);
-    print_temp("CPU System Agent", cpu_temp.system_agent);
+    print_temp(smc, "cpu.proximity", cpu_temp.proximity);
+    print_temp(smc, "cpu.die", cpu_temp.die);
+    print_temp(smc, "cpu.graphics", cpu_temp.graphics);
+    print_temp(smc, "cpu.system_agent", cpu_temp.system_agent);
     println!();
 
-    for (core_num, core_temp) in smc.cpu_core_temps()?.enumerate() {
-        print_temp(format!("CPU Core {}", core_num + 1), core_temp?);
+    let cores = smc
+        .cpu_core_temps()?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    let hybrid = cores.iter().any(|core| core.kind == CoreKind::Efficiency);
+    for core in cores {
+        let id = match (hybrid, core.kind) {
+            (false, _) => "cpu.core",
+            (true, CoreKind::Performance) => "cpu.core.performance",
+            (true, CoreKind::Efficiency) => {
+                if core.index == 0 {
+                    println!();
+                }
+                "cpu.core.efficiency"
+            }
+        };
+        let number = usize::from(core.index) + 1;
+        print_numbered_temp(smc, id, number, core.temperature);
     }
 
     Ok(())
@@ -193,9 +350,15 @@ fn print_cpu_temps(smc: &mut Smc) -> Result<()> {
 fn print_gpu_temps(smc: &mut Smc) -> Result<()> {
     println!("--- GPU Temperatures [gpu] ---");
     println!();
-    let gpu_temp = smc.gpu_temperature()?;
-    print_temp("GPU Proximity", gpu_temp.proximity);
-    print_temp("GPU Die", gpu_temp.die);
+    let gpus = smc.gpus()?.collect::<std::result::Result<Vec<_>, _>>()?;
+    for (gpu_num, gpu) in gpus.into_iter().enumerate() {
+        if gpu_num > 0 {
+            println!();
+        }
+        print_numbered_temp(smc, "gpu.proximity", gpu_num + 1, gpu.proximity);
+        print_numbered_temp(smc, "gpu.die", gpu_num + 1, gpu.die);
+        print_numbered_power(smc, "power.gpu", gpu_num + 1, gpu.power);
+    }
 
     Ok(())
 }
@@ -205,17 +368,34 @@ fn print_other_temps(smc: &mut Smc) -> Result<()> {
     println!("--- Other Temperatures [other] ---");
     println!();
     let other_temp = smc.other_temperatures()?;
-    print_temp("Mainboard Proximity", other_temp.mainboard_proximity);
-    print_temp("Platform CHD", other_temp.platform_controller_hub_die);
-    print_temp("Airport", other_temp.airport);
-    print_temp("Airflow Left", other_temp.airflow_left);
-    print_temp("Airflow Right", other_temp.airflow_right);
-    print_temp("Thunderbolt Left", other_temp.thunderbolt_left);
-    print_temp("Thunderbolt Right", other_temp.thunderbolt_right);
-    print_temp("Heatpipe 1", other_temp.heatpipe_1);
-    print_temp("Heatpipe 2", other_temp.heatpipe_2);
-    print_temp("Palm rest 1", other_temp.palm_rest_1);
-    print_temp("Palm rest 2", other_temp.palm_rest_2);
+    print_temp(smc, "mainboard.proximity", other_temp.mainboard_proximity);
+    print_temp(smc, "pch.die", other_temp.platform_controller_hub_die);
+    print_temp(smc, "airport.proximity", other_temp.airport);
+    print_temp(smc, "airflow.left", other_temp.airflow_left);
+    print_temp(smc, "airflow.right", other_temp.airflow_right);
+    print_temp(smc, "thunderbolt.left", other_temp.thunderbolt_left);
+    print_temp(smc, "thunderbolt.right", other_temp.thunderbolt_right);
+    print_temp(smc, "heatpipe.1", other_temp.heatpipe_1);
+    print_temp(smc, "heatpipe.2", other_temp.heatpipe_2);
+    print_temp(smc, "palm_rest.1", other_temp.palm_rest_1);
+    print_temp(smc, "palm_rest.2", other_temp.palm_rest_2);
+
+    let dimms = smc
+        .dimm_temperatures()?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (dimm_num, dimm) in dimms.into_iter().enumerate() {
+        print_numbered_temp(smc, "memory.proximity", dimm_num + 1, dimm.proximity);
+        print_numbered_temp(smc, "memory.slot", dimm_num + 1, dimm.slot);
+    }
+
+    let drives = smc
+        .drive_temperatures()?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for (drive_num, drive) in drives.into_iter().enumerate() {
+        print_numbered_temp(smc, "drive.proximity", drive_num + 1, drive.proximity);
+        print_numbered_temp(smc, "drive.nand_1", drive_num + 1, drive.nand_1);
+        print_numbered_temp(smc, "drive.nand_2", drive_num + 1, drive.nand_2);
+    }
 
     Ok(())
 }
@@ -224,14 +404,24 @@ fn print_other_temps(smc: &mut Smc) -> Result<()> {
 fn print_fan_speeds(smc: &mut Smc) -> Result<()> {
     println!("--- Fan Speeds [fan] ---");
     println!();
-    for (fan_num, fan_speed) in smc.fans()?.enumerate() {
-        let fan_speed = fan_speed?;
-        print_value(
-            format!("Fan {} speed", fan_num + 1),
+    let fans = smc.fans()?.collect::<std::result::Result<Vec<_>, _>>()?;
+    // a single sample cannot tell a stalled fan from one that is spinning up
+    let health = FanDiagnostics::default().update(&fans);
+    for (fan_num, (fan_speed, health)) in fans.into_iter().zip(health).enumerate() {
+        print_checked(
+            numbered_label(smc, "fan.actual", fan_num + 1),
             fan_speed.actual,
-            "RPM",
+            Unit::Rpm,
             fan_speed.thresholds(),
         );
+        let health = match health {
+            FanHealth::Ok if !fan_speed.reaches_target() || !fan_speed.health().is_ok() => {
+                String::from("Changing speed")
+            }
+            FanHealth::Ok => String::from("OK"),
+            health => format!("⚠️  {}", health),
+        };
+        print_line(numbered_label(smc, "fan.health", fan_num + 1), health);
     }
 
     Ok(())
@@ -269,37 +459,42 @@ fn print_battery_info(smc: &mut Smc) -> Result<()> {
         _ => "Magic Dust",
     };
     print_line(
-        "Battery healthy",
+        label(smc, "battery.health"),
         if battery_info.health_ok { "OK" } else { "💥" },
     );
-    print_line("Running on", running_on);
-    for battery in smc.battery_details()? {
-        let battery = battery?;
+    print_line(label(smc, "battery.source"), running_on);
+    let batteries = smc
+        .battery_details()?
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    for battery in batteries {
         if !battery_info.ac_present {
             if let Some(remaining) = battery.time_remaining() {
-                print_line("Time remainging", Time(remaining));
+                print_line(label(smc, "battery.time_remaining"), Time(remaining));
             }
         }
         if battery_info.charging {
             if let Some(until_full) = battery.time_until_full() {
-                print_line("Time until full", Time(until_full));
+                print_line(label(smc, "battery.time_until_full"), Time(until_full));
             }
         }
-        print_line("Cycle count", battery.cycles);
-        print_percentage("Charge", battery.percentage());
-        print_value_unit("Current Capacity", *battery.current_capacity, "mAh");
-        print_value_unit("Full Capacity", *battery.full_capacity, "mAh");
-        print_value_unit("Amperage", *battery.amperage, "mA");
-        print_value_unit("Voltage", *battery.voltage, "V");
+        print_line(label(smc, "battery.cycles"), battery.cycles);
+        print_percentage(label(smc, "battery.charge"), battery.percentage());
+        print_unit(
+            label(smc, "battery.capacity.current"),
+            battery.current_capacity,
+        );
+        print_unit(label(smc, "battery.capacity.full"), battery.full_capacity);
+        print_unit(label(smc, "battery.amperage"), battery.amperage);
+        print_unit(label(smc, "battery.voltage"), battery.voltage);
         if *battery.power > 0.0 {
-            print_value_unit("Power Delivery", *battery.power, "W");
+            print_unit(label(smc, "battery.power.delivery"), battery.power);
         }
         if *battery.power < 0.0 {
-            print_value_unit("Charging rate", -*battery.power, "W");
+            print_unit(label(smc, "battery.power.charging"), -battery.power);
         }
     }
-    print_temp("Battery Sensor 1", battery_info.temperature_1);
-    print_temp("Battery Sensor 2", battery_info.temperature_2);
+    print_temp(smc, "battery.temperature.1", battery_info.temperature_1);
+    print_temp(smc, "battery.temperature.2", battery_info.temperature_2);
 
     Ok(())
 }
@@ -309,23 +504,129 @@ fn print_power_consumption(smc: &mut Smc) -> Result<()> {
     println!("--- Power consumption [power] ---");
     println!();
     let cpu_power = smc.cpu_power()?;
-    print_power("CPU Core", cpu_power.core);
-    print_power("CPU DRAM", cpu_power.dram);
-    print_power("CPU Graphics", cpu_power.gfx);
-    print_power("CPU Total", cpu_power.total);
-    print_power("CPU Rail", cpu_power.rail);
-    let gpu_power = smc.gpu_power()?;
-    print_power("GPU", gpu_power);
+    print_power(smc, "power.cpu.core", cpu_power.core);
+    print_power(smc, "power.cpu.dram", cpu_power.dram);
+    print_power(smc, "power.cpu.gfx", cpu_power.gfx);
+    print_power(smc, "power.cpu.total", cpu_power.total);
+    print_power(smc, "power.cpu.rail", cpu_power.rail);
+    let gpus = smc.gpus()?.collect::<std::result::Result<Vec<_>, _>>()?;
+    for (gpu_num, gpu) in gpus.into_iter().enumerate() {
+        print_numbered_power(smc, "power.gpu", gpu_num + 1, gpu.power);
+    }
     let dc_in = smc.power_dc_in()?;
-    print_power("DC Input", dc_in);
+    print_power(smc, "power.dc_in", dc_in);
     let system_total = smc.power_system_total()?;
-    print_power("System Total", system_total);
+    print_power(smc, "power.system_total", system_total);
+    let limits = smc.power_limits()?;
+    print_line(label(smc, "limit.cpu"), limit_flag(limits.cpu_limited()));
+    print_line(label(smc, "limit.gpu"), limit_flag(limits.gpu_limited()));
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn print_voltages(smc: &mut Smc) -> Result<()> {
+    println!("--- Voltages [voltage] ---");
+    println!();
+    let voltages = smc.voltages()?;
+    for &(id, voltage) in &[
+        ("voltage.cpu_core", voltages.cpu_core),
+        ("voltage.cpu_vccio", voltages.cpu_vccio),
+        ("voltage.cpu_system_agent", voltages.cpu_system_agent),
+        ("voltage.gpu_core", voltages.gpu_core),
+        ("voltage.memory", voltages.memory),
+        ("voltage.pch", voltages.platform_controller_hub),
+        ("voltage.dc_in", voltages.dc_in),
+        ("voltage.rail_12v", voltages.rail_12v),
+    ] {
+        print_unit(label(smc, id), voltage);
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn print_currents(smc: &mut Smc) -> Result<()> {
+    println!("--- Currents [current] ---");
+    println!();
+    let currents = smc.currents()?;
+    for &(id, current) in &[
+        ("current.cpu_core", currents.cpu_core),
+        ("current.cpu_rail", currents.cpu_rail),
+        ("current.cpu_vccio", currents.cpu_vccio),
+        ("current.cpu_system_agent", currents.cpu_system_agent),
+        ("current.gpu_core", currents.gpu_core),
+        ("current.memory", currents.memory),
+        ("current.dc_in", currents.dc_in),
+        ("current.charger", currents.charger),
+    ] {
+        print_unit(label(smc, id), current);
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn print_environment(smc: &mut Smc) -> Result<()> {
+    println!("--- Environment [env] ---");
+    println!();
+    let lid_closed = smc.is_lid_closed()?;
+    print_line(
+        label(smc, "lid.closed"),
+        if lid_closed { "Closed" } else { "Open" },
+    );
+    let ambient_light = smc.ambient_light()?;
+    for (id, sensor) in [
+        ("light.left", ambient_light.left),
+        ("light.right", ambient_light.right),
+    ]
+    .iter()
+    {
+        if sensor.valid {
+            print_line(
+                label(smc, id),
+                format!("{} (IR {})", sensor.broadband, sensor.infrared),
+            );
+        }
+    }
+    let motion = smc.motion()?;
+    print_line(
+        label(smc, "motion"),
+        format!("X {:.2} Y {:.2} Z {:.2}", motion.x, motion.y, motion.z),
+    );
+
+    Ok(())
+}
+
+#[doc(hidden)]
+fn print_discovered(smc: &mut Smc) -> Result<()> {
+    println!("--- Discovered [discover] ---");
+    println!();
+    let unknown = smc
+        .discover()?
+        .into_iter()
+        .filter(|sensor| !sensor.known)
+        .collect::<Vec<_>>();
+    if unknown.is_empty() {
+        println!("All sensors are in the catalog");
+    }
+    for sensor in unknown {
+        print_unit(sensor.label, sensor.reading);
+    }
 
     Ok(())
 }
 
 #[doc(hidden)]
 fn print_all_keys(smc: &mut Smc) -> Result<()> {
+    let system_info = smc.system_info()?;
+    println!("Platform == {:?}", system_info.platform);
+    println!("Revision == {}", system_info.revision);
+    println!("Interface == {}", system_info.interface_version);
+    println!("Branch == {}", system_info.branch);
+    println!("Platform ID == {}", system_info.platform_id);
+    println!();
+
     for info in smc.all_data()? {
         let info = info?;
         match info.value {
@@ -345,13 +646,62 @@ fn print_all_keys(smc: &mut Smc) -> Result<()> {
 }
 
 #[doc(hidden)]
-fn print_temp(label: impl AsRef<str>, temp: Celsius) {
-    print_value(label, temp, "°C", Celsius::thresholds())
+fn label(smc: &Smc, id: &str) -> String {
+    smc.catalog()
+        .label_for(id, smc.platform(), smc.model())
+        .unwrap_or(id)
+        .to_string()
+}
+
+#[doc(hidden)]
+fn numbered_label(smc: &Smc, id: &str, number: usize) -> String {
+    match smc.catalog().label_for(id, smc.platform(), smc.model()) {
+        Some(label) => label.replace('#', &number.to_string()),
+        None => format!("{} {}", id, number),
+    }
+}
+
+#[doc(hidden)]
+fn print_temp(smc: &Smc, id: &str, temp: Celsius) {
+    let thresholds = smc.temperature_thresholds(id);
+    print_checked(label(smc, id), temp, Unit::Celsius, thresholds)
+}
+
+#[doc(hidden)]
+fn print_numbered_temp(smc: &Smc, id: &str, number: usize, temp: Celsius) {
+    let thresholds = smc.temperature_thresholds(id);
+    print_checked(
+        numbered_label(smc, id, number),
+        temp,
+        Unit::Celsius,
+        thresholds,
+    )
+}
+
+#[doc(hidden)]
+fn print_power(smc: &Smc, id: &str, power: Watt) {
+    let thresholds = smc.power_thresholds(id);
+    print_checked(label(smc, id), power, Unit::Watt, thresholds)
 }
 
 #[doc(hidden)]
-fn print_power(label: impl AsRef<str>, power: Watt) {
-    print_value(label, power, "W", Watt::thresholds())
+fn print_numbered_power(smc: &Smc, id: &str, number: usize, power: Watt) {
+    let thresholds = smc.power_thresholds(id);
+    print_checked(
+        numbered_label(smc, id, number),
+        power,
+        Unit::Watt,
+        thresholds,
+    )
+}
+
+#[doc(hidden)]
+fn limit_flag(limited: bool) -> &'static str {
+    if limited {
+        "🔥 Throttled"
+    } else {
+        "No"
+    }
 }
 
 #[doc(hidden)]
@@ -360,8 +710,8 @@ fn print_line(label: impl AsRef<str>, val: impl Display) {
 }
 
 #[doc(hidden)]
-fn print_value_unit(label: impl AsRef<str>, val: impl Display, unit: impl AsRef<str>) {
-    println!("{:>24}  {:8.2} {:6}", label.as_ref(), val, unit.as_ref(),);
+fn print_unit(label: impl AsRef<str>, val: impl Display) {
+    println!("{:>24}  {:8.2}", label.as_ref(), val);
 }
 
 #[doc(hidden)]
@@ -383,6 +733,25 @@ where
     );
 }
 
+#[doc(hidden)]
+fn print_checked<T>(label: impl AsRef<str>, val: T, unit: Unit, thresholds: [T; 4])
+where
+    T: Into<f64> + PartialOrd + Copy,
+{
+    let value = val.into();
+    if unit.is_plausible(value as f32) {
+        print_value(label, val, unit.symbol(), thresholds)
+    } else {
+        // grey out values of broken or missing sensors
+        println!(
+            "{:>24}  \x1B[38;5;8m{:8.2} {:6}▁▁▁▁▁▁▁▁\x1B[0m",
+            label.as_ref(),
+            value,
+            unit.symbol(),
+        );
+    }
+}
+
 #[doc(hidden)]
 fn sparkles<T>(val: T, thresholds: [T; 4]) -> String
 where